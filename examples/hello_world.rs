@@ -93,7 +93,7 @@ fn main() -> Result<()> {
         // do something if this is a mouse event
         match event.data() {
             EventData::Paint(_g) => println!("Repainting window."),
-            EventData::MouseButtonDown(coords, _button) => {
+            EventData::MouseButtonDown(coords, _button, _modifiers) => {
                 println!("Mouse click at ({}, {})", coords.x, coords.y);
             }
             EventData::KeyDown(keyinfo, _loc) => {
@@ -45,10 +45,13 @@
 
 use euclid::{default::Rect, Angle};
 use ordered_float::NotNan;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// A geometric arc. This is called a "GeometricArc" to differentiate it from the
 /// standard library type "Arc".
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GeometricArc {
     bounding_rect: Rect<u32>,
     start_angle: Angle<NotNan<f32>>,
@@ -71,8 +74,8 @@ impl GeometricArc {
     pub unsafe fn new_unchecked(bounds: Rect<u32>, start: Angle<f32>, end: Angle<f32>) -> Self {
         Self {
             bounding_rect: bounds,
-            start_angle: Angle::radians(NotNan::unchecked_new(start.radians)),
-            end_angle: Angle::radians(NotNan::unchecked_new(end.radians)),
+            start_angle: Angle::radians(NotNan::new_unchecked(start.radians)),
+            end_angle: Angle::radians(NotNan::new_unchecked(end.radians)),
         }
     }
 
@@ -106,7 +109,7 @@ impl GeometricArc {
     /// Set the angle in the ellipse where the arc starts. This does not check for NaN.
     #[inline]
     pub unsafe fn set_start_angle_unchecked(&mut self, angle: Angle<f32>) {
-        self.start_angle = Angle::radians(NotNan::unchecked_new(angle.radians));
+        self.start_angle = Angle::radians(NotNan::new_unchecked(angle.radians));
     }
 
     /// The angle in the ellipse where the arc ends.
@@ -126,6 +129,6 @@ impl GeometricArc {
     /// Set the angle in the ellipse where the arc ends. This does not check for NaN.
     #[inline]
     pub unsafe fn set_end_angle_unchecked(&mut self, angle: Angle<f32>) {
-        self.end_angle = Angle::radians(NotNan::unchecked_new(angle.radians));
+        self.end_angle = Angle::radians(NotNan::new_unchecked(angle.radians));
     }
 }
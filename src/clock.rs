@@ -0,0 +1,159 @@
+/* -----------------------------------------------------------------------------------
+ * src/clock.rs - Time sources for the event loop.
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+use crate::mutexes::Mutex;
+use alloc::sync::Arc;
+use core::{fmt, time::Duration};
+
+/// A source of time for an Instance.
+///
+/// The clock is used to timestamp events and to measure any time-based thresholds in the
+/// event loop. By default, an Instance uses the `SystemClock`. Tests can replace it with
+/// a `ManualClock` in order to make the event loop deterministic.
+pub trait Clock: Send + Sync {
+    /// The amount of time that has passed since this clock started.
+    fn now(&self) -> Duration;
+}
+
+/// A clock that follows the system's monotonic clock.
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone)]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    /// Create a new system clock, starting at the current instant.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves forward when it is told to.
+///
+/// Clones of a manual clock share the same time, so a test can give one clone to the
+/// Instance and keep another one around to advance it.
+///
+/// # Example
+///
+/// ```
+/// use beetle::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let cloned_clock = clock.clone();
+///
+/// clock.advance(Duration::from_millis(250));
+/// assert_eq!(cloned_clock.now(), Duration::from_millis(250));
+/// ```
+#[derive(Clone, Default)]
+pub struct ManualClock(Arc<Mutex<Duration>>);
+
+impl fmt::Debug for ManualClock {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ManualClock").field(&self.now()).finish()
+    }
+}
+
+impl ManualClock {
+    /// Create a new manual clock, starting at zero.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the clock forward by the specified amount of time.
+    #[inline]
+    pub fn advance(&self, by: Duration) {
+        *self.0.lock() += by;
+    }
+
+    /// Set the current time of the clock.
+    #[inline]
+    pub fn set(&self, now: Duration) {
+        *self.0.lock() = now;
+    }
+}
+
+impl Clock for ManualClock {
+    #[inline]
+    fn now(&self) -> Duration {
+        *self.0.lock()
+    }
+}
+
+// the clock used when none is specified
+#[inline]
+pub(crate) fn default_clock() -> Arc<dyn Clock> {
+    cfg_if::cfg_if! {
+        if #[cfg(feature = "std")] {
+            Arc::new(SystemClock::new())
+        } else {
+            // without std, there is no monotonic clock to fall back on
+            Arc::new(ManualClock::new())
+        }
+    }
+}
@@ -44,9 +44,18 @@
  */
 
 use crate::InvalidColor;
+#[cfg(feature = "serde")]
+use core::convert::TryFrom;
 use ordered_float::NotNan;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "ColorComponents", into = "ColorComponents")
+)]
 pub struct Color {
     r: NotNan<f32>,
     g: NotNan<f32>,
@@ -56,7 +65,7 @@ pub struct Color {
 
 #[inline]
 fn checked_cnvrt(fe: f32) -> crate::Result<NotNan<f32>> {
-    if -1.0 <= fe && fe <= 1.0 {
+    if (-1.0..=1.0).contains(&fe) {
         Ok(NotNan::new(fe)?)
     } else {
         Err(crate::Error::InvalidColor(InvalidColor::OutOfRange(fe)))
@@ -79,10 +88,10 @@ impl Color {
     #[inline]
     pub unsafe fn new_no_nan_check(r: f32, g: f32, b: f32, a: f32) -> Self {
         Self {
-            r: NotNan::unchecked_new(r),
-            g: NotNan::unchecked_new(g),
-            b: NotNan::unchecked_new(b),
-            a: NotNan::unchecked_new(a),
+            r: NotNan::new_unchecked(r),
+            g: NotNan::new_unchecked(g),
+            b: NotNan::new_unchecked(b),
+            a: NotNan::new_unchecked(a),
         }
     }
 
@@ -120,7 +129,7 @@ impl Color {
     /// Set the red component of this color without checking for invalid values.
     #[inline]
     pub unsafe fn set_r_unchecked(&mut self, val: f32) {
-        self.r = NotNan::unchecked_new(val);
+        self.r = NotNan::new_unchecked(val);
     }
 
     /// Set the green component of this color.
@@ -133,7 +142,7 @@ impl Color {
     /// Set the green component of this color without checking for invalid values.
     #[inline]
     pub unsafe fn set_g_unchecked(&mut self, val: f32) {
-        self.g = NotNan::unchecked_new(val);
+        self.g = NotNan::new_unchecked(val);
     }
 
     /// Set the blue component of this color.
@@ -146,7 +155,7 @@ impl Color {
     /// Set the blue component of this color without checking for invalid values.
     #[inline]
     pub unsafe fn set_b_unchecked(&mut self, val: f32) {
-        self.b = NotNan::unchecked_new(val);
+        self.b = NotNan::new_unchecked(val);
     }
 
     /// Set the alpha component of this color.
@@ -159,7 +168,7 @@ impl Color {
     /// Set the alpha component of this color without checking for invalid values.
     #[inline]
     pub unsafe fn set_a_unchecked(&mut self, val: f32) {
-        self.a = NotNan::unchecked_new(val);
+        self.a = NotNan::new_unchecked(val);
     }
 
     /// Create a new color from RGB bytes.
@@ -167,7 +176,7 @@ impl Color {
     pub fn from_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
         macro_rules! cnvrt_u8_f32 {
             ($val: expr) => {{
-                ($val as f32) / (core::u8::MAX as f32)
+                ($val as f32) / (u8::MAX as f32)
             }};
         }
 
@@ -182,6 +191,40 @@ impl Color {
     }
 }
 
+// the raw components of a color, used to make sure deserialized colors go through
+// the same range checks as Color::new
+#[cfg(feature = "serde")]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+struct ColorComponents {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+}
+
+#[cfg(feature = "serde")]
+impl From<Color> for ColorComponents {
+    #[inline]
+    fn from(c: Color) -> Self {
+        Self {
+            r: c.r(),
+            g: c.g(),
+            b: c.b(),
+            a: c.a(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ColorComponents> for Color {
+    type Error = crate::Error;
+
+    #[inline]
+    fn try_from(c: ColorComponents) -> crate::Result<Self> {
+        Color::new(c.r, c.g, c.b, c.a)
+    }
+}
+
 /// Several common colors.
 pub mod colors {
     use super::Color;
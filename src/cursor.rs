@@ -0,0 +1,101 @@
+/* -----------------------------------------------------------------------------------
+ * src/cursor.rs - Mouse cursor shapes
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+use crate::PixelBuffer;
+use alloc::sync::Arc;
+use euclid::default::Point2D;
+
+/// The shape of the mouse cursor while it is over a window.
+#[derive(Debug, Clone)]
+pub enum Cursor {
+    /// The standard arrow.
+    Arrow,
+    /// The I-beam used over editable text.
+    IBeam,
+    /// The pointing hand used over links.
+    Hand,
+    /// The application is busy.
+    Wait,
+    /// A crosshair, used for precise selection.
+    Crosshair,
+    /// Resizing to the left or right.
+    ResizeHorizontal,
+    /// Resizing up or down.
+    ResizeVertical,
+    /// Resizing from the top-left or bottom-right corner.
+    ResizeNwSe,
+    /// Resizing from the top-right or bottom-left corner.
+    ResizeNeSw,
+    /// Moving something in any direction.
+    Move,
+    /// The action is not allowed.
+    NotAllowed,
+    /// No cursor is shown.
+    Hidden,
+    /// A cursor made from an image. The hotspot is the pixel of the image that points
+    /// at things.
+    Custom {
+        image: Arc<PixelBuffer>,
+        hotspot: Point2D<u32>,
+    },
+}
+
+impl Default for Cursor {
+    #[inline]
+    fn default() -> Self {
+        Self::Arrow
+    }
+}
+
+impl Cursor {
+    /// Create a cursor from an image and the pixel of the image that points at things.
+    #[inline]
+    pub fn custom(image: PixelBuffer, hotspot: Point2D<u32>) -> Self {
+        Self::Custom {
+            image: Arc::new(image),
+            hotspot,
+        }
+    }
+}
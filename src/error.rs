@@ -85,6 +85,17 @@ pub enum Error {
     WindowIDNoDowncast,
     UnableToWrite,
     UnableToRead,
+
+    /// The X server rejected a request.
+    #[cfg(target_os = "linux")]
+    Protocol {
+        error_code: u8,
+        request_code: u8,
+        minor_code: u8,
+        resource_id: u64,
+    },
+    /// The connection to the windowing system was lost.
+    ConnectionLost,
 }
 
 impl fmt::Display for Error {
@@ -113,6 +124,18 @@ impl fmt::Display for Error {
             Self::UnableToWrite => f.pad("Unable to write to RwLock"),
             Self::UnableToRead => f.pad("Unable to read from RwLock"),
             Self::InvalidColor(ref i) => fmt::Display::fmt(i, f),
+            #[cfg(target_os = "linux")]
+            Self::Protocol {
+                error_code,
+                request_code,
+                minor_code,
+                resource_id,
+            } => write!(
+                f,
+                "X protocol error {} in request {}.{} on resource {:#x}",
+                error_code, request_code, minor_code, resource_id
+            ),
+            Self::ConnectionLost => f.pad("Lost the connection to the windowing system"),
             _ => unreachable!(),
         }
     }
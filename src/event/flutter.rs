@@ -44,21 +44,199 @@
  */
 
 use super::{Event, EventData};
-use crate::{Graphics, Instance, KeyInfo, KeyType, MouseButton, Window};
-use core::convert::TryInto;
-use euclid::default::Point2D;
-use flutterbug::{prelude::*, Atom, Event as FEvent, EventType as FEventType, FunctionKeys};
+use crate::{
+    ButtonState, Graphics, Instance, KeyInfo, KeyType, MouseButton, PhysicalKey, Window,
+    WindowState,
+};
+use alloc::string::String;
+use core::{convert::TryInto, mem::MaybeUninit, ptr};
+use cty::{c_int, c_uint};
+use euclid::default::{Point2D, Vector2D};
+use flutterbug::{prelude::*, x11::xlib, Atom, Event as FEvent, EventType as FEventType};
 use smallvec::SmallVec;
 
+// convert the X11 state mask into the set of pressed mouse buttons. buttons 4 and 5 are
+// the scroll wheel, which is never held down
+#[inline]
+fn button_state(state: c_uint) -> ButtonState {
+    [
+        (xlib::Button1Mask, ButtonState::BUTTON1),
+        (xlib::Button2Mask, ButtonState::BUTTON2),
+        (xlib::Button3Mask, ButtonState::BUTTON3),
+    ]
+    .iter()
+    .filter(|(mask, _)| state & mask != 0)
+    .fold(ButtonState::empty(), |bs, (_, button)| bs | *button)
+}
+
+// query the current position of the pointer relative to a window, which also tells
+// the X server that we're ready for the next motion hint
+#[inline]
+fn query_pointer(instance: &Instance, window: &Window) -> crate::Result<(Point2D<i32>, c_uint)> {
+    let dpy = instance.raw_display()?;
+    let win = window.inner_window()?.inner_flutter_window().window();
+
+    let mut root = MaybeUninit::<xlib::Window>::uninit();
+    let mut child = MaybeUninit::<xlib::Window>::uninit();
+    let mut root_x: c_int = 0;
+    let mut root_y: c_int = 0;
+    let mut x: c_int = 0;
+    let mut y: c_int = 0;
+    let mut state: c_uint = 0;
+
+    unsafe {
+        xlib::XQueryPointer(
+            dpy,
+            win,
+            root.as_mut_ptr(),
+            child.as_mut_ptr(),
+            &mut root_x,
+            &mut root_y,
+            &mut x,
+            &mut y,
+            &mut state,
+        )
+    };
+
+    Ok((Point2D::new(x, y), state))
+}
+
+// figure out the origin of a window from a ConfigureNotify event
+//
+// the coordinates in a ConfigureNotify event are relative to the parent window. for
+// top-level windows, the parent is usually a frame owned by the window manager, so we
+// translate them to the root window instead. synthetic events sent by the window manager
+// are already root-relative.
+#[inline]
+fn configure_origin(
+    instance: &Instance,
+    window: &Window,
+    x: c_int,
+    y: c_int,
+    synthetic: bool,
+) -> crate::Result<Point2D<i32>> {
+    let (x, y) = if synthetic || !window.is_top_level()? {
+        (x, y)
+    } else {
+        let dpy = instance.raw_display()?;
+        let win = window.inner_window()?.inner_flutter_window().window();
+
+        let mut root_x: c_int = 0;
+        let mut root_y: c_int = 0;
+        let mut child = MaybeUninit::<xlib::Window>::uninit();
+        unsafe {
+            xlib::XTranslateCoordinates(
+                dpy,
+                win,
+                xlib::XDefaultRootWindow(dpy),
+                0,
+                0,
+                &mut root_x,
+                &mut root_y,
+                child.as_mut_ptr(),
+            )
+        };
+        (root_x, root_y)
+    };
+
+    Ok(Point2D::new(x, y))
+}
+
+// tell if a window is the same as, or a descendant of, another window
+fn is_descendant(dpy: *mut xlib::Display, mut win: xlib::Window, ancestor: xlib::Window) -> bool {
+    loop {
+        if win == ancestor {
+            return true;
+        }
+
+        let mut root: xlib::Window = 0;
+        let mut parent: xlib::Window = 0;
+        let mut children: *mut xlib::Window = ptr::null_mut();
+        let mut nchildren: c_uint = 0;
+        let res = unsafe {
+            xlib::XQueryTree(
+                dpy,
+                win,
+                &mut root,
+                &mut parent,
+                &mut children,
+                &mut nchildren,
+            )
+        };
+        if !children.is_null() {
+            unsafe { xlib::XFree(children as *mut _) };
+        }
+
+        if res == 0 || parent == 0 || parent == root {
+            return false;
+        }
+        win = parent;
+    }
+}
+
+// read the _NET_WM_STATE property of a window to figure out its display state, and
+// whether it's fullscreen and always on top
+fn net_wm_state(instance: &Instance, window: &Window) -> crate::Result<(WindowState, bool, bool)> {
+    let win = window.inner_window()?.inner_flutter_window().window();
+    let atoms = instance.flutterbug_atom_property(win, "_NET_WM_STATE")?;
+    let has = |name| -> crate::Result<bool> { Ok(atoms.contains(&instance.atom(name)?)) };
+
+    let state = if has("_NET_WM_STATE_HIDDEN")? {
+        WindowState::Minimized
+    } else if has("_NET_WM_STATE_MAXIMIZED_VERT")? && has("_NET_WM_STATE_MAXIMIZED_HORZ")? {
+        WindowState::Maximized
+    } else {
+        WindowState::Normal
+    };
+
+    Ok((
+        state,
+        has("_NET_WM_STATE_FULLSCREEN")?,
+        has("_NET_WM_STATE_ABOVE")?,
+    ))
+}
+
 impl Event {
     /// Translate a Flutterbug event to a Beetle event.
     pub(crate) fn from_flutter(
         instance: &Instance,
         fev: FEvent,
     ) -> crate::Result<SmallVec<[Self; 2]>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("from_flutter", kind = ?fev.kind()).entered();
+
         // optimize for at least two events
         // TODO: this can probably be a TinyVec, if we want to go that route
         let mut evs = SmallVec::new();
+
+        // give the input method a chance to swallow the event, e.g. the key presses that
+        // make up a dead key or compose sequence
+        let mut raw = fev.inner()?;
+        let filter_window = unsafe { raw.any.window };
+        if unsafe { xlib::XFilterEvent(&mut raw, filter_window) } != 0 {
+            return Ok(evs);
+        }
+
+        // changes to the keyboard mapping aren't associated with a window
+        if let FEvent::Mapping(ref m) = fev {
+            if m.request() == xlib::MappingKeyboard || m.request() == xlib::MappingModifier {
+                unsafe { xlib::XRefreshKeyboardMapping(&mut raw.mapping) };
+            }
+            if m.request() == xlib::MappingModifier {
+                instance.flutterbug_invalidate_modifiers();
+            }
+
+            if m.request() == xlib::MappingKeyboard {
+                if let Some(target) = instance
+                    .focused_window()
+                    .or_else(|| instance.flutterbug_any_top_level_window())
+                {
+                    evs.push(Event::new(&target, EventData::KeymapChanged));
+                }
+            }
+            return Ok(evs);
+        }
+
         let ty = fev.kind();
         let assoc_window: Window = match instance.flutterbug_get_window(fev.window()) {
             Some(w) => w,
@@ -69,59 +247,126 @@ impl Event {
             }
         };
 
-        log::debug!("Translating Flutterbug Event: {:?}", &fev);
+        log::debug!("Translating Flutterbug Event: {:?}", fev);
+
+        // nothing else matters once the window is gone
+        if raw.get_type() == xlib::DestroyNotify {
+            evs.extend(assoc_window.destroyed());
+            return Ok(evs);
+        }
 
         match fev {
             // X11 events involving a key press
             FEvent::Key(k) => {
+                // without detectable autorepeat, held keys produce a release right before
+                // every repeated press
+                if let FEventType::KeyRelease = ty {
+                    if instance.flutterbug_is_fake_release(k.keycode(), k.time())? {
+                        return Ok(evs);
+                    }
+                }
+
+                // keyboard input belongs to the focused window, even if the X server
+                // delivered it to one of its ancestors
+                let (target, retargeted) = match instance.focused_window() {
+                    Some(fw) if fw != assoc_window => (fw, true),
+                    _ => (assoc_window.clone(), false),
+                };
+
                 // get the key information from the event
-                let (ks, _char_rep) = k.lookup_utf8(&*assoc_window.inner_window()?.ic())?;
-                let mut ki = KeyInfo::new(KeyType::from_keysym(
-                    ks.ok_or_else(|| crate::Error::KeysymNotFound)?,
-                ));
+                let (ks, char_rep) = k.lookup_utf8(assoc_window.inner_window()?.ic())?;
 
-                // set function key info
-                #[inline]
-                fn fn_key_info<F>(
-                    ki: &mut KeyInfo,
-                    k: &flutterbug::KeyEvent,
-                    key: FunctionKeys,
-                    setter: F,
-                ) where
-                    F: FnOnce(&mut KeyInfo),
-                {
-                    if k.has_function(key) {
-                        setter(ki);
+                // the text composed by the input method, minus any control characters,
+                // since those are better handled through KeyDown
+                let text = match ty {
+                    FEventType::KeyPress => char_rep
+                        .map(|s| s.chars().filter(|c| !c.is_control()).collect::<String>())
+                        .filter(|s| !s.is_empty()),
+                    _ => None,
+                };
+
+                // the input method may produce text without a keysym, e.g. at the end of a
+                // compose sequence
+                let (ks, text) = match (ks, text) {
+                    (Some(ks), text) => (ks, text),
+                    (None, Some(text)) => {
+                        evs.push(Event::new(&target, EventData::TextInput(text)));
+                        return Ok(evs);
                     }
+                    (None, None) => return Err(crate::Error::KeysymNotFound),
+                };
+                let mut ki = KeyInfo::new(KeyType::from_keysym(ks));
+
+                // X11 key codes are evdev key codes plus 8
+                let keycode = k.keycode();
+                ki.set_scancode(keycode);
+                ki.set_physical_key(PhysicalKey::from_evdev(keycode.saturating_sub(8)));
+
+                // a press of a key that is already down is an autorepeat
+                let is_press = matches!(ty, FEventType::KeyPress);
+                let repeat = instance.flutterbug_track_key(keycode, is_press);
+                ki.set_repeat(repeat);
+
+                // switching between layouts changes the XKB group, which is stored in bits
+                // 13 and 14 of the state
+                let group = (k.state() >> 13) & 0x3;
+                if instance.flutterbug_update_keyboard_group(group) {
+                    evs.push(Event::new(&target, EventData::KeymapChanged));
                 }
 
-                fn_key_info(&mut ki, &k, FunctionKeys::CONTROL, |k| k.set_ctrl(true));
-                fn_key_info(&mut ki, &k, FunctionKeys::ALT, |k| k.set_alt(true));
-                fn_key_info(&mut ki, &k, FunctionKeys::SHIFT, |k| k.set_shift(true));
+                // set modifier and mouse button info
+                ki.set_modifiers(instance.flutterbug_modifiers(k.state())?);
+                ki.set_buttons(button_state(k.state()));
 
                 // key press mouse location
-                let loc: Option<Point2D<u32>> =
-                    if let (Ok(x), Ok(y)) = (k.x().try_into(), k.y().try_into()) {
-                        Some(Point2D::new(x, y))
-                    } else {
-                        None
-                    };
+                // if the event was retargeted, the location is relative to the wrong window
+                let loc: Option<Point2D<u32>> = if let (false, Ok(x), Ok(y)) =
+                    (retargeted, k.x().try_into(), k.y().try_into())
+                {
+                    Some(Point2D::new(x, y))
+                } else {
+                    None
+                };
 
+                if !(repeat && instance.key_repeat_filter()) {
+                    evs.push(Event::new(
+                        &target,
+                        match ty {
+                            FEventType::KeyPress => EventData::KeyDown(ki, loc),
+                            FEventType::KeyRelease => EventData::KeyUp(ki, loc),
+                            _ => unreachable!(),
+                        },
+                    ));
+                }
+
+                if let Some(text) = text {
+                    evs.push(Event::new(&target, EventData::TextInput(text)));
+                }
+            }
+            // Re-rendering of the window
+            // Note: the expose rectangle is only the damaged region, not the geometry
+            FEvent::Expose(_) => {
                 evs.push(Event::new(
                     &assoc_window,
-                    match ty {
-                        FEventType::KeyPress => EventData::KeyDown(ki, loc),
-                        FEventType::KeyRelease => EventData::KeyUp(ki, loc),
-                        _ => unreachable!(),
-                    },
+                    EventData::Paint(Graphics::from_window(&assoc_window)?),
                 ));
+                // TODO: create g-object
             }
-            // Re-rendering of the window
-            FEvent::Expose(e) => {
-                // if the size isn't the same, set up a changed bounds event
+            // The window's geometry has changed
+            FEvent::Configure(c) => {
                 let old_bounds = assoc_window.bounds()?;
-                let new_bounds =
-                    euclid::rect(e.x().try_into()?, e.y().try_into()?, e.width(), e.height());
+                let synthetic = c.from_send_event();
+                let origin = configure_origin(instance, &assoc_window, c.x(), c.y(), synthetic)?;
+                evs.extend(assoc_window.update_position(origin));
+
+                // bounds are unsigned, so windows partially off the left or top of the screen
+                // are clamped to the edge there. the real position is kept separately
+                let new_bounds = euclid::rect(
+                    origin.x.max(0) as u32,
+                    origin.y.max(0) as u32,
+                    c.width(),
+                    c.height(),
+                );
 
                 if old_bounds != new_bounds {
                     // Note: The (false, true) at the end tells the event handler that
@@ -139,51 +384,202 @@ impl Event {
                     ev.set_hidden_data((false, true));
                     evs.push(ev);
                 }
-
-                evs.push(Event::new(
-                    &assoc_window,
-                    EventData::Paint(Graphics::from_window(&assoc_window)?),
-                ));
-                // TODO: create g-object
             }
             // Press/release of a mouse button
             #[allow(non_upper_case_globals)]
             FEvent::Button(b) => {
                 use flutterbug::x11::xlib::{Button1, Button2, Button3, Button4, Button5};
-                if let (Ok(x), Ok(y)) = (b.x().try_into(), b.y().try_into()) {
-                    let button = match b.button() {
-                        Button1 => MouseButton::Button1,
-                        Button2 => MouseButton::Button2,
-                        Button3 => MouseButton::Button3,
-                        Button4 => MouseButton::Button4,
-                        Button5 => MouseButton::Button5,
-                        _ => return Err(crate::Error::StaticMsg("Unexpected X11 mouse input")),
+                // while the pointer is grabbed, and after a button is pressed, the
+                // pointer can be anywhere on the screen
+                let loc = Point2D::new(b.x(), b.y());
+                let modifiers = instance.flutterbug_modifiers(b.state())?;
+
+                // buttons 4 through 7 are the scroll wheel
+                // only the press is interesting, the release comes right after it
+                let delta = match b.button() {
+                    Button4 => Some(Vector2D::new(0.0, 1.0)),
+                    Button5 => Some(Vector2D::new(0.0, -1.0)),
+                    6 => Some(Vector2D::new(-1.0, 0.0)),
+                    7 => Some(Vector2D::new(1.0, 0.0)),
+                    _ => None,
+                };
+                if let Some(delta) = delta {
+                    // scrolling outside of the window doesn't scroll it
+                    if let (FEventType::ButtonPress, Ok(x), Ok(y)) =
+                        (b.kind(), loc.x.try_into(), loc.y.try_into())
+                    {
+                        evs.push(Event::new(
+                            &assoc_window,
+                            EventData::Scroll {
+                                delta,
+                                location: Point2D::new(x, y),
+                                modifiers,
+                            },
+                        ));
+                    }
+                    return Ok(evs);
+                }
+
+                let button = match b.button() {
+                    Button1 => MouseButton::Button1,
+                    Button2 => MouseButton::Button2,
+                    Button3 => MouseButton::Button3,
+                    8 => MouseButton::Back,
+                    9 => MouseButton::Forward,
+                    other => MouseButton::Other(other.try_into().unwrap_or(u8::MAX)),
+                };
+
+                evs.push(Event::new(
+                    &assoc_window,
+                    match b.kind() {
+                        FEventType::ButtonPress => {
+                            EventData::MouseButtonDown(loc, button, modifiers)
+                        }
+                        FEventType::ButtonRelease => {
+                            EventData::MouseButtonUp(loc, button, modifiers)
+                        }
+                        _ => unreachable!(),
+                        // First element is the X/Y coordinates. Second is the mouse button pressed.
+                        // Third is the set of modifier keys.
+                    },
+                ));
+            }
+            // Movement of the mouse
+            FEvent::Motion(m) => {
+                // in relative mouse mode, motion that was queued up before the pointer was
+                // moved back to the center would be measured from the center. hints look
+                // up where the pointer is now, so they're fine
+                if m.is_hint() == 0
+                    && assoc_window.is_relative_mouse()
+                    && assoc_window
+                        .inner_window()?
+                        .is_before_warp(unsafe { raw.any.serial })
+                {
+                    return Ok(evs);
+                }
+
+                let (loc, state) = if m.is_hint() != 0 {
+                    query_pointer(instance, &assoc_window)?
+                } else {
+                    (Point2D::new(m.x(), m.y()), m.state())
+                };
+
+                evs.push(Event::new(
+                    &assoc_window,
+                    EventData::MouseMove(
+                        loc,
+                        button_state(state),
+                        instance.flutterbug_modifiers(state)?,
+                    ),
+                ));
+            }
+            // The mouse entering or leaving the window
+            FEvent::Crossing(c) => evs.push(Event::new(
+                &assoc_window,
+                match c.kind() {
+                    FEventType::EnterNotify => EventData::MouseEnter,
+                    FEventType::LeaveNotify => EventData::MouseLeave,
+                    _ => unreachable!(),
+                },
+            )),
+            // The window gaining or losing the keyboard focus
+            FEvent::FocusChange(f) => {
+                // focus changes involving the pointer window are noise, and the virtual
+                // ones are only sent to ancestors of the window that actually has the focus
+                let ignored = [
+                    xlib::NotifyPointer,
+                    xlib::NotifyVirtual,
+                    xlib::NotifyNonlinearVirtual,
+                ];
+                if !ignored.contains(&f.detail()) {
+                    let focused = match f.kind() {
+                        FEventType::FocusIn => true,
+                        FEventType::FocusOut => false,
+                        _ => unreachable!(),
                     };
-                    let loc = Point2D::<u32>::new(x, y);
+                    instance.set_focused(&assoc_window, focused);
+                    if focused {
+                        instance.flutterbug_sync_pressed_keys()?;
+                    }
 
                     evs.push(Event::new(
                         &assoc_window,
-                        match b.kind() {
-                            FEventType::ButtonPress => EventData::MouseButtonDown(loc, button),
-                            FEventType::ButtonRelease => EventData::MouseButtonUp(loc, button),
-                            _ => unreachable!(),
-                            // First element is the X/Y coordinates. Second is the mouse button pressed.
+                        if focused {
+                            EventData::FocusIn
+                        } else {
+                            EventData::FocusOut
                         },
                     ));
                 }
             }
+            // The window being shown or hidden
+            FEvent::Map(_) => evs.extend(assoc_window.update_status(|s| s.mapped = true)),
+            FEvent::Unmap(_) => evs.extend(assoc_window.update_status(|s| s.mapped = false)),
+            FEvent::Visibility(v) => {
+                let obscured = v.state() == xlib::VisibilityFullyObscured;
+                evs.extend(assoc_window.update_status(|s| s.obscured = obscured));
+            }
+            // The window manager changing the state of the window
+            FEvent::Property(p) => {
+                if p.atom() == instance.atom("_NET_WM_STATE")? {
+                    let (state, fullscreen, above) = net_wm_state(instance, &assoc_window)?;
+                    evs.extend(assoc_window.update_status(|s| {
+                        s.state = state;
+                        s.fullscreen = fullscreen;
+                        s.above = above;
+                    }));
+                }
+            }
             // Special client messages
             FEvent::ClientMessage(c) => {
-                // Check if the client message corresponds to the pre-set delete window atom
-                if AsRef::<[Atom]>::as_ref(&c.data())[0] == instance.delete_window_atom() {
+                // the first element of a WM_PROTOCOLS message is the protocol in question
+                let data = c.data();
+                let data = AsRef::<[Atom]>::as_ref(&data);
+                if c.message_type() != instance.atom("WM_PROTOCOLS")? {
+                    // not one of ours
+                } else if data[0] == instance.atom("WM_DELETE_WINDOW")? {
                     evs.push(Event::new(&assoc_window, EventData::Close));
 
-                    // also send a quit event if this is the top-level window
-                    if assoc_window.is_top_level()? {
+                    // also send a quit event if the quit policy says so
+                    if instance.window_closed(&assoc_window)? {
                         let mut quit_ev = Event::new(&assoc_window, EventData::Quit);
                         quit_ev.set_is_exit_event(true);
                         evs.push(quit_ev);
                     }
+                } else if data[0] == instance.atom("_NET_WM_PING")? {
+                    // the window manager is checking that we're still alive. send the
+                    // message back to the root window to answer it
+                    let dpy = instance.raw_display()?;
+                    let root = unsafe { xlib::XDefaultRootWindow(dpy) };
+                    unsafe {
+                        if raw.client_message.window != root {
+                            raw.client_message.window = root;
+                            xlib::XSendEvent(
+                                dpy,
+                                root,
+                                xlib::False,
+                                xlib::SubstructureNotifyMask | xlib::SubstructureRedirectMask,
+                                &mut raw,
+                            );
+                            xlib::XFlush(dpy);
+                        }
+                    }
+                } else if data[0] == instance.atom("WM_TAKE_FOCUS")? {
+                    // the window manager wants us to pick which window gets the focus. give it
+                    // to the window that had it last, if it's a part of this top-level window
+                    let dpy = instance.raw_display()?;
+                    let top_level = assoc_window.inner_window()?.inner_flutter_window().window();
+                    let mut win = top_level;
+                    if let Some(fw) = instance.focused_window() {
+                        let fw = fw.inner_window()?.inner_flutter_window().window();
+                        if is_descendant(dpy, fw, top_level) {
+                            win = fw;
+                        }
+                    }
+
+                    unsafe {
+                        xlib::XSetInputFocus(dpy, win, xlib::RevertToParent, data[1] as xlib::Time)
+                    };
                 }
             }
             _ => { /* TODO: don't ignore these! */ }
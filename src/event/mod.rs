@@ -43,10 +43,15 @@
  * ----------------------------------------------------------------------------------
  */
 
-use crate::{Graphics, KeyInfo, MouseButton, Texture, Window};
+use crate::{
+    stats::timed, ButtonState, Graphics, KeyInfo, Modifiers, MouseButton, Texture, Window,
+};
 use alloc::{string::String, sync::Arc, vec, vec::Vec};
-use core::{any::Any, fmt, option::Option};
-use euclid::default::{Point2D, Rect};
+use core::{any::Any, fmt, option::Option, time::Duration};
+use euclid::default::{Point2D, Rect, Size2D, Vector2D};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 
 #[cfg(target_os = "linux")]
 mod flutter;
@@ -74,10 +79,26 @@ pub enum EventType {
     Quit,
     /// A single window is closing.
     Close,
+    /// A window has been destroyed.
+    Destroyed,
     /// The window's bounds are being changed.
     BoundsChanging,
     /// The window's bounds have changed.
     BoundsChanged,
+    /// The window's origin has changed.
+    Moved,
+    /// The window's size has changed.
+    Resized,
+    /// The window has become visible.
+    Shown,
+    /// The window is no longer visible.
+    Hidden,
+    /// The window has been minimized.
+    Minimized,
+    /// The window has been maximized.
+    Maximized,
+    /// The window has been restored from being minimized or maximized.
+    Restored,
     /// The window's background is being changed.
     BackgroundChanging,
     /// The window's background has been changed.
@@ -86,6 +107,32 @@ pub enum EventType {
     MouseButtonDown,
     /// The window has had a mouse button released on it.
     MouseButtonUp,
+    /// The mouse has moved over the window.
+    MouseMove,
+    /// The mouse has moved while the window is in relative mouse mode.
+    MouseDelta,
+    /// The mouse has entered the window.
+    MouseEnter,
+    /// The mouse has left the window.
+    MouseLeave,
+    /// The mouse wheel has been scrolled over the window.
+    Scroll,
+    /// The window has received the keyboard focus.
+    FocusIn,
+    /// The window has lost the keyboard focus.
+    FocusOut,
+    /// Text has been typed into the window.
+    TextInput,
+    /// The keyboard layout has changed.
+    KeymapChanged,
+    /// A mouse button has been clicked, possibly several times in a row.
+    Click,
+    /// The mouse has started being dragged.
+    DragStart,
+    /// The mouse is being dragged.
+    DragMove,
+    /// The mouse has stopped being dragged.
+    DragEnd,
     /// A manual, integer event.
     Integer(usize),
     /// A manual, string event.
@@ -93,7 +140,13 @@ pub enum EventType {
 }
 
 /// Types of data deployed from Beetle.
+///
+/// With the `serde` feature enabled, this can be serialized and deserialized. The
+/// exceptions are `Paint` and `BackgroundChanging`, which carry graphics state that
+/// only makes sense inside of the current process, and `Str`, which can be
+/// serialized but not deserialized.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EventData {
     /// Nothing is happening. Often used as a transport for event data.
     NoOp,
@@ -104,6 +157,7 @@ pub enum EventData {
     /// The window is about to be repainted.
     AboutToPaint,
     /// The window is being repainted.
+    #[cfg_attr(feature = "serde", serde(skip))]
     Paint(Graphics),
     /// The text of a window is currently changing.
     TextChanging { old: String, new: String },
@@ -113,25 +167,112 @@ pub enum EventData {
     Quit,
     /// A single window is closing.
     Close,
+    /// A window has been destroyed.
+    Destroyed,
     /// The window's bounds are being changed.
     BoundsChanging { old: Rect<u32>, new: Rect<u32> },
     /// The window's bounds have changed.
     BoundsChanged { old: Rect<u32>, new: Rect<u32> },
+    /// The window's position has changed. Unlike the origin of its bounds, the position
+    /// may be negative if the window is partially off the left or top of the screen.
+    Moved {
+        old: Point2D<i32>,
+        new: Point2D<i32>,
+    },
+    /// The window's size has changed. This is emitted alongside `BoundsChanged`.
+    Resized { old: Size2D<u32>, new: Size2D<u32> },
+    /// The window has become visible. This happens when it is mapped, uncovered or
+    /// restored from being minimized.
+    Shown,
+    /// The window is no longer visible. This happens when it is unmapped, completely
+    /// covered by other windows or minimized.
+    Hidden,
+    /// The window has been minimized.
+    Minimized,
+    /// The window has been maximized.
+    Maximized,
+    /// The window has been restored from being minimized or maximized.
+    Restored,
     /// The window's background is being changed.
+    #[cfg_attr(feature = "serde", serde(skip))]
     BackgroundChanging {
         old: Option<Texture>,
         new: Option<Texture>,
     },
     /// The window's background has been changed.
     BackgroundChanged,
-    /// The window has had a mouse button depressed on it.
-    MouseButtonDown(Point2D<u32>, MouseButton),
-    /// The window has had a mouse button released on it.
-    MouseButtonUp(Point2D<u32>, MouseButton),
+    /// The window has had a mouse button depressed on it. The location may be outside of
+    /// the window while the pointer is grabbed. The last element is the set of modifier
+    /// keys that were held down.
+    MouseButtonDown(Point2D<i32>, MouseButton, Modifiers),
+    /// A mouse button that was pressed on the window has been released. The location may
+    /// be outside of the window, and negative. The last element is the set of modifier
+    /// keys that were held down.
+    MouseButtonUp(Point2D<i32>, MouseButton, Modifiers),
+    /// The mouse has moved over the window. The first element is the location of the
+    /// mouse relative to the window, which may be negative if a button is held down and
+    /// the mouse has been dragged outside of the window. The second element is the set
+    /// of buttons that are currently held down, and the third is the set of modifier
+    /// keys that are held down.
+    MouseMove(Point2D<i32>, ButtonState, Modifiers),
+    /// The mouse has moved while the window is in relative mouse mode. This replaces
+    /// `MouseMove`, and the delta is not limited by the edges of the window or screen.
+    MouseDelta(Vector2D<i32>),
+    /// The mouse has entered the window.
+    MouseEnter,
+    /// The mouse has left the window.
+    MouseLeave,
+    /// The mouse wheel has been scrolled over the window. The delta is measured in wheel
+    /// clicks; a positive Y scrolls up, and a positive X scrolls right. Consecutive wheel
+    /// clicks are merged into a single event.
+    Scroll {
+        delta: Vector2D<f32>,
+        location: Point2D<u32>,
+        modifiers: Modifiers,
+    },
+    /// The window has received the keyboard focus.
+    FocusIn,
+    /// The window has lost the keyboard focus.
+    FocusOut,
+    /// Text has been typed into the window. This is emitted after the `KeyDown` event
+    /// that produced it, and accounts for the keyboard layout, dead keys and compose
+    /// sequences. Control characters are never included.
+    TextInput(String),
+    /// The keyboard layout or the keyboard mapping has changed. Use
+    /// `Instance::keyboard_layout` to get the new layout.
+    KeymapChanged,
+    /// A mouse button has been pressed and released without the mouse moving much. `count`
+    /// is 2 for a double click, 3 for a triple click, and so on. The location is where the
+    /// button was pressed.
+    Click {
+        button: MouseButton,
+        location: Point2D<u32>,
+        count: u32,
+    },
+    /// The mouse has moved far enough with a button held down to count as a drag.
+    DragStart {
+        button: MouseButton,
+        origin: Point2D<u32>,
+    },
+    /// The mouse has moved during a drag. The location is relative to the window the drag
+    /// started in, and may be negative.
+    DragMove {
+        button: MouseButton,
+        origin: Point2D<u32>,
+        location: Point2D<i32>,
+    },
+    /// The mouse button that started a drag has been released.
+    DragEnd {
+        button: MouseButton,
+        origin: Point2D<u32>,
+        location: Point2D<i32>,
+    },
     /// A manual, integer event.
     Integer(usize),
     /// A manual, string event.
-    Str(&'static str),
+    // the field has to be skipped as well, or deserializing any event would need 'static input
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Str(#[cfg_attr(feature = "serde", serde(skip_deserializing))] &'static str),
 }
 
 impl EventData {
@@ -148,12 +289,52 @@ impl EventData {
             EventData::TextChanged { old: _, new: _ } => EventType::TextChanged,
             EventData::Quit => EventType::Quit,
             EventData::Close => EventType::Close,
+            EventData::Destroyed => EventType::Destroyed,
             EventData::BoundsChanging { old: _, new: _ } => EventType::BoundsChanging,
             EventData::BoundsChanged { old: _, new: _ } => EventType::BoundsChanged,
+            EventData::Moved { old: _, new: _ } => EventType::Moved,
+            EventData::Resized { old: _, new: _ } => EventType::Resized,
+            EventData::Shown => EventType::Shown,
+            EventData::Hidden => EventType::Hidden,
+            EventData::Minimized => EventType::Minimized,
+            EventData::Maximized => EventType::Maximized,
+            EventData::Restored => EventType::Restored,
             EventData::BackgroundChanging { old: _, new: _ } => EventType::BackgroundChanging,
             EventData::BackgroundChanged => EventType::BackgroundChanged,
-            EventData::MouseButtonDown(ref _p, ref _b) => EventType::MouseButtonDown,
-            EventData::MouseButtonUp(ref _p, ref _b) => EventType::MouseButtonUp,
+            EventData::MouseButtonDown(ref _p, ref _b, ref _m) => EventType::MouseButtonDown,
+            EventData::MouseButtonUp(ref _p, ref _b, ref _m) => EventType::MouseButtonUp,
+            EventData::MouseMove(ref _p, ref _b, ref _m) => EventType::MouseMove,
+            EventData::MouseDelta(ref _d) => EventType::MouseDelta,
+            EventData::MouseEnter => EventType::MouseEnter,
+            EventData::MouseLeave => EventType::MouseLeave,
+            EventData::Scroll {
+                delta: _,
+                location: _,
+                modifiers: _,
+            } => EventType::Scroll,
+            EventData::FocusIn => EventType::FocusIn,
+            EventData::FocusOut => EventType::FocusOut,
+            EventData::TextInput(_) => EventType::TextInput,
+            EventData::KeymapChanged => EventType::KeymapChanged,
+            EventData::Click {
+                button: _,
+                location: _,
+                count: _,
+            } => EventType::Click,
+            EventData::DragStart {
+                button: _,
+                origin: _,
+            } => EventType::DragStart,
+            EventData::DragMove {
+                button: _,
+                origin: _,
+                location: _,
+            } => EventType::DragMove,
+            EventData::DragEnd {
+                button: _,
+                origin: _,
+                location: _,
+            } => EventType::DragEnd,
             EventData::Integer(id) => EventType::Integer(*id),
             EventData::Str(id) => EventType::Str(id),
         }
@@ -167,6 +348,7 @@ pub struct Event {
     arguments: Vec<Arc<dyn Any + Send + Sync + 'static>>,
     hidden_data: Option<Arc<dyn Any + Send + Sync + 'static>>,
     needs_quit: bool,
+    timestamp: Duration,
 }
 
 impl fmt::Debug for Event {
@@ -175,15 +357,21 @@ impl fmt::Debug for Event {
             .field("target_window", &self.target_window)
             .field("data", &self.data)
             .field("needs_quit", &self.needs_quit)
+            .field("timestamp", &self.timestamp)
             .finish()
     }
 }
 
 impl Event {
-    /// Create a new event from its raw parts.
+    /// Create a new event from its raw parts. The event is timestamped using the clock
+    /// of the window's instance.
     #[inline]
     pub fn new(target_window: &Window, data: EventData) -> Self {
         Self {
+            timestamp: target_window
+                .try_instance()
+                .map(|i| i.now())
+                .unwrap_or_default(),
             target_window: target_window.clone(),
             data,
             arguments: vec![],
@@ -217,6 +405,18 @@ impl Event {
         &self.data
     }
 
+    /// Get the time at which this event was created, according to the instance's clock.
+    #[inline]
+    pub fn timestamp(&self) -> Duration {
+        self.timestamp
+    }
+
+    /// Set the time at which this event was created.
+    #[inline]
+    pub fn set_timestamp(&mut self, timestamp: Duration) {
+        self.timestamp = timestamp;
+    }
+
     /// Get the window that this event targets.
     #[inline]
     pub fn window(&self) -> &Window {
@@ -240,10 +440,76 @@ impl Event {
         self.arguments.push(Arc::new(arg));
     }
 
+    /// Merge another event into this one, if the two can be combined. Returns true if
+    /// the other event was merged.
+    ///
+    /// Currently, only scroll events that target the same window can be merged.
+    #[inline]
+    pub(crate) fn merge(&mut self, other: &Event) -> bool {
+        if self.target_window != other.target_window {
+            return false;
+        }
+
+        match (&mut self.data, &other.data) {
+            (
+                EventData::Scroll {
+                    ref mut delta,
+                    location: _,
+                    modifiers: _,
+                },
+                EventData::Scroll {
+                    delta: ref other_delta,
+                    location: _,
+                    modifiers: _,
+                },
+            ) => {
+                *delta += *other_delta;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Create the events describing a finished change of a window's bounds: a
+    /// `BoundsChanged` event, followed by a `Resized` event if the size actually changed.
+    /// `Moved` events come from the backend, which knows the window's signed position.
+    pub(crate) fn bounds_changed(
+        window: &Window,
+        old: Rect<u32>,
+        new: Rect<u32>,
+    ) -> SmallVec<[Event; 3]> {
+        let mut evs = SmallVec::new();
+        evs.push(Event::new(window, EventData::BoundsChanged { old, new }));
+
+        if old.size != new.size {
+            evs.push(Event::new(
+                window,
+                EventData::Resized {
+                    old: old.size,
+                    new: new.size,
+                },
+            ));
+        }
+
+        evs
+    }
+
     /// Dispatch its event to the system handling source.
     #[inline]
     pub fn dispatch(&self) -> crate::Result<()> {
-        self.window().handle_event(self)
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("dispatch", window = self.window().id(), ty = ?self.ty())
+            .entered();
+
+        match (self.data(), self.window().try_instance()) {
+            (EventData::Paint(_), Some(instance)) => {
+                let clock = instance.clock();
+                let (res, elapsed) = timed(&*clock, || self.window().handle_event(self));
+                instance.record_paint_time(elapsed);
+                res
+            }
+            _ => self.window().handle_event(self),
+        }
     }
 
     /// Tell if the event requires the application to exit.
@@ -258,3 +524,35 @@ impl Event {
         self.needs_quit = is_quit;
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+#[test]
+fn test_event_data_serde() {
+    use crate::{Modifiers, MouseButton};
+    use alloc::format;
+
+    let data = [
+        EventData::Quit,
+        EventData::Moved {
+            old: Point2D::new(-10, 20),
+            new: Point2D::new(5, 6),
+        },
+        EventData::MouseButtonUp(Point2D::new(-3, 4), MouseButton::Back, Modifiers::SHIFT),
+        EventData::Scroll {
+            delta: Vector2D::new(0.0, -2.0),
+            location: Point2D::new(7, 8),
+            modifiers: Modifiers::empty(),
+        },
+        EventData::TextInput(String::from("héllo 🐝")),
+        EventData::Integer(42),
+    ];
+    for d in data.iter() {
+        let json = serde_json::to_string(d).unwrap();
+        let back: EventData = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", back), format!("{:?}", d));
+    }
+
+    // static strings can only go one way
+    let json = serde_json::to_string(&EventData::Str("beetle")).unwrap();
+    assert!(serde_json::from_str::<EventData>(&json).is_err());
+}
@@ -44,10 +44,9 @@
  */
 
 use super::{Event, EventData};
-use crate::{Instance, KeyInfo, KeyType, Window};
-use alloc::{boxed::Box, sync::Arc, vec};
+use crate::{Instance, KeyInfo, KeyType, Modifiers, PhysicalKey, Window, WindowState};
+use alloc::{boxed::Box, string::String, sync::Arc, vec};
 use core::{convert::TryInto, mem, ptr, sync::atomic::AtomicPtr};
-use cty::c_int;
 use euclid::default::{Point2D, Rect, Size2D};
 use porcupine::{
     prelude::*,
@@ -71,6 +70,9 @@ impl Event {
         wparam: WPARAM,
         lparam: LPARAM,
     ) -> crate::Result<SmallVec<[Event; 2]>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("from_porc", msg).entered();
+
         let mut evs: SmallVec<[Event; 2]> = SmallVec::new();
 
         log::debug!("Received windows message: {}", msg);
@@ -94,12 +96,16 @@ impl Event {
             WM_CLOSE => {
                 log::debug!("Found WM_CLOSE message");
                 evs.push(Event::new(&assoc_window, EventData::Close));
-                if assoc_window.is_top_level()? {
+                if instance.window_closed(&assoc_window)? {
                     let mut qm = Event::new(&assoc_window, EventData::Quit);
                     qm.set_is_exit_event(true);
                     evs.push(qm);
                 }
             }
+            WM_DESTROY => {
+                log::debug!("Found WM_DESTROY message");
+                evs.extend(assoc_window.destroyed());
+            }
             WM_PAINT => {
                 log::debug!("Found WM_PAINT message");
                 evs.push(Event::new(
@@ -113,19 +119,38 @@ impl Event {
                 let key_stroke = wparam;
                 let mut ki = KeyInfo::new(KeyType::from_vk(key_stroke));
 
-                // set key information
-                fn set_key_state<F>(ki: &mut KeyInfo, test_for: c_int, setter: F)
-                where
-                    F: FnOnce(&mut KeyInfo),
-                {
-                    if unsafe { GetKeyState(test_for) } & core::i16::MAX != 0 {
-                        setter(ki);
-                    }
+                // bits 16-23 hold the scan code, bit 24 tells if it's an extended key
+                let scancode = ((lparam >> 16) & 0xff) as u32;
+                let extended = (lparam >> 24) & 0x1 != 0;
+                ki.set_scancode(scancode);
+                ki.set_physical_key(PhysicalKey::from_scancode(scancode, extended));
+
+                // bit 30 tells if the key was already down before this message
+                let repeat = match msg {
+                    WM_KEYDOWN | WM_SYSKEYDOWN => (lparam >> 30) & 0x1 != 0,
+                    _ => false,
+                };
+                ki.set_repeat(repeat);
+                if repeat && instance.key_repeat_filter() {
+                    return Ok(evs);
                 }
 
-                set_key_state(&mut ki, VK_CONTROL, |ki| ki.set_ctrl(true));
-                set_key_state(&mut ki, VK_MENU, |ki| ki.set_alt(true));
-                set_key_state(&mut ki, VK_SHIFT, |ki| ki.set_shift(true));
+                // set key information
+                // the high bit tells if the key is down, the low bit tells if it's toggled
+                let modifiers = [
+                    (VK_SHIFT, 0x8000, Modifiers::SHIFT),
+                    (VK_CONTROL, 0x8000, Modifiers::CTRL),
+                    (VK_MENU, 0x8000, Modifiers::ALT),
+                    (VK_LWIN, 0x8000, Modifiers::SUPER),
+                    (VK_RWIN, 0x8000, Modifiers::SUPER),
+                    (VK_CAPITAL, 0x0001, Modifiers::CAPS_LOCK),
+                    (VK_NUMLOCK, 0x0001, Modifiers::NUM_LOCK),
+                    (VK_SCROLL, 0x0001, Modifiers::SCROLL_LOCK),
+                ]
+                .iter()
+                .filter(|(vk, mask, _)| unsafe { GetKeyState(*vk) } as u16 & mask != 0)
+                .fold(Modifiers::empty(), |mods, (_, _, m)| mods | *m);
+                ki.set_modifiers(modifiers);
 
                 let loc: Option<Point2D<u32>> = match porcupine::cursor_pos().and_then(|f| {
                     assoc_window
@@ -157,6 +182,44 @@ impl Event {
                     },
                 ));
             }
+            WM_SETFOCUS | WM_KILLFOCUS => {
+                let focused = msg == WM_SETFOCUS;
+                instance.set_focused(&assoc_window, focused);
+                evs.push(Event::new(
+                    &assoc_window,
+                    if focused {
+                        EventData::FocusIn
+                    } else {
+                        EventData::FocusOut
+                    },
+                ));
+            }
+            WM_INPUTLANGCHANGE => {
+                evs.push(Event::new(&assoc_window, EventData::KeymapChanged));
+            }
+            WM_CHAR => match assoc_window.porcupine_decode_char(wparam as u16) {
+                Some(c) if !c.is_control() => {
+                    let mut text = String::new();
+                    text.push(c);
+                    evs.push(Event::new(&assoc_window, EventData::TextInput(text)));
+                }
+                _ => (),
+            },
+            WM_SHOWWINDOW => {
+                let shown = wparam != 0;
+                evs.extend(assoc_window.update_status(|s| s.mapped = shown));
+            }
+            WM_SIZE => {
+                let state = match wparam {
+                    SIZE_MINIMIZED => Some(WindowState::Minimized),
+                    SIZE_MAXIMIZED => Some(WindowState::Maximized),
+                    SIZE_RESTORED => Some(WindowState::Normal),
+                    _ => None,
+                };
+                if let Some(state) = state {
+                    evs.extend(assoc_window.update_status(|s| s.state = state));
+                }
+            }
             WM_WINDOWPOSCHANGING => {
                 let new_bounds = get_newbounds(lparam)?;
                 let old_bounds = assoc_window.bounds()?;
@@ -188,13 +251,12 @@ impl Event {
                     }
                 };
 
-                evs.push(Event::new(
-                    &assoc_window,
-                    EventData::BoundsChanged {
-                        old: old_bounds,
-                        new: new_bounds,
-                    },
-                ));
+                evs.extend(Event::bounds_changed(&assoc_window, old_bounds, new_bounds));
+
+                let wp: WINDOWPOS = unsafe { ptr::read(lparam as LPWINDOWPOS) };
+                if wp.flags & SWP_NOMOVE == 0 {
+                    evs.extend(assoc_window.update_position(Point2D::new(wp.x, wp.y)));
+                }
             }
             _ => {
                 log::debug!("Unsupported message.");
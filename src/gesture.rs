@@ -0,0 +1,338 @@
+/* -----------------------------------------------------------------------------------
+ * src/gesture.rs - Click counting and drag detection on top of raw mouse events.
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+use crate::{EventData, MouseButton};
+use core::time::Duration;
+use euclid::default::Point2D;
+
+/// The thresholds used to turn raw mouse button events into clicks and drags.
+///
+/// # Example
+///
+/// ```no_run
+/// use beetle::{GestureConfig, Instance};
+/// use core::time::Duration;
+///
+/// # fn main() -> beetle::Result<()> {
+/// let instance = Instance::new()?;
+/// instance.set_gesture_config(
+///     GestureConfig::new()
+///         .with_click_time(Duration::from_millis(400))
+///         .with_drag_threshold(8),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GestureConfig {
+    click_time: Duration,
+    click_distance: u32,
+    drag_threshold: u32,
+}
+
+impl Default for GestureConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            click_time: Duration::from_millis(500),
+            click_distance: 4,
+            drag_threshold: 4,
+        }
+    }
+}
+
+impl GestureConfig {
+    /// Create a new gesture configuration with the default thresholds.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The longest amount of time between two clicks for them to count as a double click.
+    #[inline]
+    pub fn click_time(&self) -> Duration {
+        self.click_time
+    }
+
+    /// Set the longest amount of time between two clicks of a multi-click.
+    #[inline]
+    pub fn with_click_time(mut self, click_time: Duration) -> Self {
+        self.click_time = click_time;
+        self
+    }
+
+    /// The furthest, in pixels along either axis, that two clicks can be apart for them
+    /// to count as a double click.
+    #[inline]
+    pub fn click_distance(&self) -> u32 {
+        self.click_distance
+    }
+
+    /// Set the furthest that two clicks of a multi-click can be apart.
+    #[inline]
+    pub fn with_click_distance(mut self, click_distance: u32) -> Self {
+        self.click_distance = click_distance;
+        self
+    }
+
+    /// How far, in pixels along either axis, the mouse has to move while a button is held
+    /// down before it counts as a drag.
+    #[inline]
+    pub fn drag_threshold(&self) -> u32 {
+        self.drag_threshold
+    }
+
+    /// Set how far the mouse has to move before it counts as a drag.
+    #[inline]
+    pub fn with_drag_threshold(mut self, drag_threshold: u32) -> Self {
+        self.drag_threshold = drag_threshold;
+        self
+    }
+}
+
+// the furthest two points are apart along either axis
+#[inline]
+fn distance(a: Point2D<i32>, b: Point2D<i32>) -> u32 {
+    let d = b - a;
+    d.x.abs().max(d.y.abs()) as u32
+}
+
+#[derive(Debug)]
+struct Press {
+    window: usize,
+    button: MouseButton,
+    origin: Point2D<i32>,
+    time: Duration,
+    dragging: bool,
+}
+
+#[derive(Debug)]
+struct LastClick {
+    window: usize,
+    button: MouseButton,
+    location: Point2D<i32>,
+    time: Duration,
+    count: u32,
+}
+
+/// Keeps track of the state needed to synthesize gestures from raw mouse events.
+#[derive(Debug, Default)]
+pub(crate) struct GestureTracker {
+    config: GestureConfig,
+    press: Option<Press>,
+    last_click: Option<LastClick>,
+}
+
+impl GestureTracker {
+    #[inline]
+    pub(crate) fn config(&self) -> GestureConfig {
+        self.config
+    }
+
+    #[inline]
+    pub(crate) fn set_config(&mut self, config: GestureConfig) {
+        self.config = config;
+    }
+
+    /// A mouse button has been pressed.
+    pub(crate) fn button_down(
+        &mut self,
+        window: usize,
+        button: MouseButton,
+        location: Point2D<i32>,
+        time: Duration,
+    ) {
+        // only the first button held down takes part in gestures, and only if it was
+        // pressed inside of the window rather than somewhere else during a pointer grab
+        if self.press.is_none() && location.x >= 0 && location.y >= 0 {
+            self.press = Some(Press {
+                window,
+                button,
+                origin: location,
+                time,
+                dragging: false,
+            });
+        }
+    }
+
+    /// The mouse has moved.
+    pub(crate) fn motion(&mut self, window: usize, location: Point2D<i32>) -> Option<EventData> {
+        let threshold = self.config.drag_threshold;
+        let press = self.press.as_mut().filter(|p| p.window == window)?;
+        let origin = press.origin.cast::<u32>();
+
+        if press.dragging {
+            Some(EventData::DragMove {
+                button: press.button,
+                origin,
+                location,
+            })
+        } else if distance(press.origin, location) > threshold {
+            press.dragging = true;
+            Some(EventData::DragStart {
+                button: press.button,
+                origin,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// A mouse button has been released.
+    pub(crate) fn button_up(
+        &mut self,
+        window: usize,
+        button: MouseButton,
+        location: Point2D<i32>,
+    ) -> Option<EventData> {
+        match self.press {
+            Some(ref p) if p.window == window && p.button == button => (),
+            _ => return None,
+        }
+        let press = self.press.take().unwrap();
+
+        if press.dragging {
+            self.last_click = None;
+            return Some(EventData::DragEnd {
+                button,
+                origin: press.origin.cast::<u32>(),
+                location,
+            });
+        }
+
+        // the mouse may have moved far without any motion events in between
+        let config = self.config;
+        if distance(press.origin, location) > config.drag_threshold {
+            self.last_click = None;
+            return None;
+        }
+
+        // presses that follow a click closely enough add to its count
+        let follows = |lc: &LastClick| {
+            lc.window == window
+                && lc.button == button
+                && distance(lc.location, press.origin) <= config.click_distance
+                && press
+                    .time
+                    .checked_sub(lc.time)
+                    .is_some_and(|d| d <= config.click_time)
+        };
+        let count = match self.last_click {
+            Some(ref lc) if follows(lc) => lc.count + 1,
+            _ => 1,
+        };
+
+        self.last_click = Some(LastClick {
+            window,
+            button,
+            location: press.origin,
+            time: press.time,
+            count,
+        });
+
+        Some(EventData::Click {
+            button,
+            location: press.origin.cast::<u32>(),
+            count,
+        })
+    }
+}
+
+#[test]
+fn test_gesture_tracker() {
+    let ms = Duration::from_millis;
+    let pt = Point2D::new;
+    let mut gt = GestureTracker::default();
+
+    // three quick clicks in the same spot make a triple click
+    for (i, time) in [0, 200, 400].iter().enumerate() {
+        gt.button_down(1, MouseButton::Button1, pt(10, 10), ms(*time));
+        match gt.button_up(1, MouseButton::Button1, pt(11, 10)) {
+            Some(EventData::Click { count, .. }) => assert_eq!(count as usize, i + 1),
+            other => panic!("Expected a click, found {:?}", other),
+        }
+    }
+
+    // a slow click starts counting over
+    gt.button_down(1, MouseButton::Button1, pt(10, 10), ms(2000));
+    match gt.button_up(1, MouseButton::Button1, pt(10, 10)) {
+        Some(EventData::Click { count: 1, .. }) => (),
+        other => panic!("Expected a single click, found {:?}", other),
+    }
+
+    // releasing far from the press isn't a click, even without motion in between
+    gt.button_down(1, MouseButton::Button1, pt(10, 10), ms(2500));
+    assert!(gt.button_up(1, MouseButton::Button1, pt(50, 10)).is_none());
+
+    // small movements don't start a drag, large ones do
+    gt.button_down(1, MouseButton::Button1, pt(10, 10), ms(3000));
+    assert!(gt.motion(1, pt(12, 12)).is_none());
+    match gt.motion(1, pt(30, 10)) {
+        Some(EventData::DragStart { .. }) => (),
+        other => panic!("Expected the start of a drag, found {:?}", other),
+    }
+    match gt.motion(1, pt(-5, 10)) {
+        Some(EventData::DragMove { location, .. }) => assert_eq!(location, pt(-5, 10)),
+        other => panic!("Expected a drag, found {:?}", other),
+    }
+    match gt.button_up(1, MouseButton::Button1, pt(-5, 10)) {
+        Some(EventData::DragEnd { .. }) => (),
+        other => panic!("Expected the end of a drag, found {:?}", other),
+    }
+
+    // a release outside of the window ends the press, so the next click still counts
+    gt.button_down(1, MouseButton::Button1, pt(10, 10), ms(4000));
+    assert!(gt.button_up(1, MouseButton::Button1, pt(-40, -3)).is_none());
+    gt.button_down(1, MouseButton::Button1, pt(10, 10), ms(4100));
+    match gt.button_up(1, MouseButton::Button1, pt(10, 10)) {
+        Some(EventData::Click { count: 1, .. }) => (),
+        other => panic!("Expected a single click, found {:?}", other),
+    }
+
+    // presses outside of the window, while the pointer is grabbed, aren't gestures
+    gt.button_down(1, MouseButton::Button1, pt(-20, 10), ms(5000));
+    assert!(gt.motion(1, pt(30, 10)).is_none());
+    assert!(gt.button_up(1, MouseButton::Button1, pt(30, 10)).is_none());
+}
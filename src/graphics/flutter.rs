@@ -108,7 +108,7 @@ impl FlutterbugGraphics {
                 macro_rules! f2us {
                     ($a: expr) => {{
                         // TODO: not naively assume the c_ushort = u16
-                        ($a * (core::u16::MAX as f32)) as u16
+                        ($a * (u16::MAX as f32)) as u16
                     }};
                 }
 
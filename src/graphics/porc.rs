@@ -72,7 +72,7 @@ impl PorcupineGraphics {
     pub fn new(wnd: &Window) -> crate::Result<Self> {
         let dc = wnd.inner_window()?.inner_porc_window().begin_paint()?;
         dc.set_pen_color(0, 0, 0)?;
-        dc.set_brush_color(core::u8::MAX, core::u8::MAX, core::u8::MAX)?;
+        dc.set_brush_color(u8::MAX, u8::MAX, u8::MAX)?;
 
         Ok(Self {
             dc,
@@ -95,7 +95,7 @@ impl PorcupineGraphics {
 fn clip_color(clr: Color) -> (u8, u8, u8) {
     macro_rules! cnvrt {
         ($val: expr) => {{
-            ($val * (core::u8::MAX as f32)) as u8
+            ($val * (u8::MAX as f32)) as u8
         }};
     }
 
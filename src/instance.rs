@@ -44,28 +44,83 @@
  */
 
 use crate::{
+    clock::default_clock,
+    gesture::GestureTracker,
     mutexes::{Mutex, RwLock},
-    Event, EventData, GenericWindowInternal, Texture, Window,
+    window::WindowStatus,
+    Clock, Event, EventData, GenericWindowInternal, GestureConfig, InstanceStats, KeyType,
+    KeyboardLayout, Modifiers, PhysicalKey, Texture, Window, WindowBuilder,
 };
-use alloc::{collections::VecDeque, string::String, sync::Arc};
-use core::{fmt, mem, option::Option};
-use euclid::default::Rect;
+use alloc::{
+    collections::VecDeque,
+    string::String,
+    sync::{Arc, Weak},
+};
+use core::{fmt, mem, option::Option, time::Duration};
+use euclid::default::{Point2D, Rect, Vector2D};
+#[cfg(target_os = "linux")]
+use flutterbug::prelude::*;
 use hashbrown::{HashMap, HashSet};
 #[cfg(windows)]
 use porcupine::prelude::*;
 use smallvec::SmallVec;
 
+/// Decides when closing windows should cause the application to quit.
+///
+/// When the application should quit, a `Quit` event is emitted after the `Close` event.
+/// `is_exit_event()` returns true for this event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum QuitPolicy {
+    /// Quit as soon as any top-level window is closed. This is the default.
+    FirstTopLevelClosed,
+    /// Quit once every top-level window has been closed.
+    LastWindowClosed,
+    /// Never quit automatically. The application is responsible for deciding when to
+    /// exit, usually by watching for `Close` events.
+    Manual,
+}
+
+impl Default for QuitPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::FirstTopLevelClosed
+    }
+}
+
 struct InstanceInternal {
     event_queue: Mutex<VecDeque<Event>>,
+    stats: Mutex<InstanceStats>,
+    clock: RwLock<Arc<dyn Clock>>,
+    quit_policy: Mutex<QuitPolicy>,
+    open_top_levels: Mutex<HashSet<usize>>,
+    focused_window: Mutex<Option<usize>>,
+    key_repeat_filter: Mutex<bool>,
+    gestures: Mutex<GestureTracker>,
 
     #[cfg(target_os = "linux")]
     window_mappings: Mutex<HashMap<WindowID, Window>>,
     #[cfg(target_os = "linux")]
     connection: flutterbug::Display,
     #[cfg(target_os = "linux")]
-    atoms: [flutterbug::Atom; 1],
+    atoms: Mutex<HashMap<&'static str, flutterbug::Atom>>,
     #[cfg(target_os = "linux")]
     im: flutterbug::InputMethod,
+    #[cfg(target_os = "linux")]
+    signal_pipe: Mutex<Option<SignalPipe>>,
+    #[cfg(target_os = "linux")]
+    pending_signals: Mutex<SmallVec<[Signal; 2]>>,
+    #[cfg(target_os = "linux")]
+    keyboard_group: Mutex<Option<u32>>,
+    #[cfg(target_os = "linux")]
+    xkb_event_base: Option<cty::c_int>,
+    #[cfg(target_os = "linux")]
+    modifier_map: Mutex<Option<[Modifiers; 8]>>,
+    #[cfg(target_os = "linux")]
+    pressed_keys: Mutex<HashSet<u32>>,
+    #[cfg(target_os = "linux")]
+    font_cursors: Mutex<HashMap<cty::c_uint, xlib::Cursor>>,
+    #[cfg(target_os = "linux")]
+    detectable_repeat: bool,
 
     #[cfg(windows)]
     window_mappings: Mutex<HashMap<usize, Window>>,
@@ -106,6 +161,50 @@ impl Clone for Instance {
     }
 }
 
+/// A reference to an Instance that does not keep it alive. Windows hold these, so that
+/// the windows in the window mappings don't keep their own Instance alive.
+#[derive(Debug, Clone)]
+#[repr(transparent)]
+pub(crate) struct WeakInstance(Weak<InstanceInternal>);
+
+impl WeakInstance {
+    /// Get the Instance, if it hasn't been dropped yet.
+    #[inline]
+    pub(crate) fn upgrade(&self) -> Option<Instance> {
+        self.0.upgrade().map(Instance)
+    }
+}
+
+impl Drop for InstanceInternal {
+    fn drop(&mut self) {
+        // destroy the windows that are still around. destroying a top-level window
+        // destroys its children as well
+        let windows = mem::take(&mut *self.window_mappings.lock());
+        windows
+            .values()
+            .filter(|w| !w.is_destroyed() && w.is_top_level().unwrap_or(false))
+            .for_each(|w| {
+                if let Err(e) = w.destroy() {
+                    log::error!("Unable to destroy window {}: {}", w.id(), e);
+                }
+            });
+
+        // the queued events hold on to windows as well
+        self.event_queue.lock().clear();
+
+        // the cached cursors aren't freed along with the windows that used them
+        #[cfg(target_os = "linux")]
+        {
+            let cursors = mem::take(&mut *self.font_cursors.lock());
+            if let Ok(dpy) = self.connection.raw() {
+                cursors.values().for_each(|cursor| unsafe {
+                    xlib::XFreeCursor(dpy.as_ptr(), *cursor);
+                });
+            }
+        }
+    }
+}
+
 impl Instance {
     /// Create the default instance of the Beetle GUI factory.
     ///
@@ -126,6 +225,8 @@ impl Instance {
 
     /// Create a new window. This function initializes the window (or equivalent) in
     /// the backend that the Instance is targeting.
+    ///
+    /// For more control over how the window is created, use a `WindowBuilder`.
     #[inline]
     pub fn create_window(
         &self,
@@ -134,34 +235,103 @@ impl Instance {
         bounds: Rect<u32>,
         background: Option<Texture>,
     ) -> crate::Result<Window> {
+        let mut builder = WindowBuilder::new().with_text(text).with_bounds(bounds);
+        if let Some(parent) = parent {
+            builder = builder.with_parent(parent);
+        }
+        if let Some(background) = background {
+            builder = builder.with_background(background);
+        }
+
+        self.build_window(builder)
+    }
+
+    /// Create a new window from a WindowBuilder.
+    pub(crate) fn build_window(&self, builder: WindowBuilder) -> crate::Result<Window> {
         #[inline]
-        fn create_window_np(
-            this: &Instance,
-            parent: Option<&Window>,
-            text: String,
-            bounds: Rect<u32>,
-            background: Option<Texture>,
-        ) -> crate::Result<Window> {
+        fn create_window_np(this: &Instance, builder: WindowBuilder) -> crate::Result<Window> {
             cfg_if::cfg_if! {
                 if #[cfg(target_os = "linux")] {
-                    this.flutterbug_create_window(parent, text, bounds, background, parent.is_none())
+                    this.flutterbug_create_window(builder)
                 } else if #[cfg(windows)] {
-                    this.porcupine_create_window(parent, text, bounds, background, parent.is_none())
+                    this.porcupine_create_window(builder)
                 } else {
                     unimplemented!()
                 }
             }
         }
 
-        let w = create_window_np(self, parent, text, bounds, background)?;
+        #[cfg(target_os = "linux")]
+        crate::xerror::check()?;
+
+        let bounds = builder.bounds;
+        let is_top_level = builder.parent.is_none();
+        let visible = builder.visible;
+
+        let w = create_window_np(self, builder)?;
         w.set_bounds(bounds)?;
+
+        if is_top_level {
+            self.0.open_top_levels.lock().insert(w.id());
+        }
+
+        // signals that arrived before there were any windows can be delivered now
+        #[cfg(target_os = "linux")]
+        {
+            let evs = self.signal_events(&[]);
+            self.0.event_queue.lock().extend(evs);
+        }
+
+        if visible {
+            w.show()?;
+        }
+
         Ok(w)
     }
 
+    /// Wait for the windowing system to process every request made so far, and return
+    /// the first error that any of them caused.
+    ///
+    /// Otherwise, errors are only reported by `next_event`, `create_window` and
+    /// `WindowBuilder::build`. Calls that change a window, such as `Window::set_cursor`
+    /// or `Window::grab_pointer`, never return the protocol errors they cause.
+    #[inline]
+    pub fn check_errors(&self) -> crate::Result<()> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                if !crate::xerror::connection_lost() {
+                    unsafe { xlib::XSync(self.raw_display()?, xlib::False) };
+                }
+                crate::xerror::check()
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Get a snapshot of the keyboard layout that is currently active.
+    #[inline]
+    pub fn keyboard_layout(&self) -> crate::Result<KeyboardLayout> {
+        cfg_if::cfg_if! {
+            if #[cfg(target_os = "linux")] {
+                self.flutterbug_keyboard_layout()
+            } else if #[cfg(windows)] {
+                self.porcupine_keyboard_layout()
+            } else {
+                unimplemented!()
+            }
+        }
+    }
+
     /// Queue an event into the event queue.
     #[inline]
     pub fn queue_event(&self, ev: Event) {
-        self.0.event_queue.lock().push_back(ev);
+        let mut evq = self.0.event_queue.lock();
+        evq.push_back(ev);
+        let depth = evq.len();
+        mem::drop(evq);
+
+        self.0.stats.lock().set_queue_depth(depth);
     }
 
     /// Queue several events into the event queue.
@@ -169,16 +339,237 @@ impl Instance {
     pub fn queue_events<I: IntoIterator<Item = Event>>(&self, evs: I) {
         let mut evq = self.0.event_queue.lock();
         evs.into_iter().for_each(|e| evq.push_back(e));
+        let depth = evq.len();
+        mem::drop(evq);
+
+        self.0.stats.lock().set_queue_depth(depth);
+    }
+
+    /// Set the policy that decides when closing windows causes the application to quit.
+    #[inline]
+    pub fn set_quit_policy(&self, policy: QuitPolicy) {
+        *self.0.quit_policy.lock() = policy;
+    }
+
+    /// Get the policy that decides when closing windows causes the application to quit.
+    #[inline]
+    pub fn quit_policy(&self) -> QuitPolicy {
+        *self.0.quit_policy.lock()
+    }
+
+    /// Set whether or not `KeyDown` events caused by holding a key down are dropped.
+    ///
+    /// This is off by default. Even when it is on, the `TextInput` events produced by
+    /// autorepeat are still delivered, so that text fields keep working as expected.
+    #[inline]
+    pub fn set_key_repeat_filter(&self, filter: bool) {
+        *self.0.key_repeat_filter.lock() = filter;
+    }
+
+    /// Tell whether or not autorepeated `KeyDown` events are dropped.
+    #[inline]
+    pub fn key_repeat_filter(&self) -> bool {
+        *self.0.key_repeat_filter.lock()
+    }
+
+    /// Set the thresholds used to recognize clicks and drags.
+    #[inline]
+    pub fn set_gesture_config(&self, config: GestureConfig) {
+        self.0.gestures.lock().set_config(config);
+    }
+
+    /// Get the thresholds used to recognize clicks and drags.
+    #[inline]
+    pub fn gesture_config(&self) -> GestureConfig {
+        self.0.gestures.lock().config()
+    }
+
+    /// Add `Click` and `Drag*` events after the raw mouse events that caused them.
+    ///
+    /// This runs before events are filtered, so gestures work even if the window doesn't
+    /// receive the raw mouse events.
+    fn synthesize_gestures(&self, evs: &mut SmallVec<[Event; 2]>) {
+        let mut gestures = self.0.gestures.lock();
+        let mut i = 0;
+        while i < evs.len() {
+            let ev = &evs[i];
+            let id = ev.window().id();
+            let gesture = match *ev.data() {
+                EventData::MouseButtonDown(loc, button, _) => {
+                    gestures.button_down(id, button, loc, ev.timestamp());
+                    None
+                }
+                EventData::MouseButtonUp(loc, button, _) => gestures.button_up(id, button, loc),
+                EventData::MouseMove(loc, _, _) => gestures.motion(id, loc),
+                _ => None,
+            };
+
+            i += 1;
+            if let Some(gesture) = gesture {
+                let gev = Event::new(evs[i - 1].window(), gesture);
+                evs.insert(i, gev);
+                i += 1;
+            }
+        }
+    }
+
+    /// Move the mouse pointer to a location relative to a window.
+    #[inline]
+    pub fn set_pointer_position(
+        &self,
+        window: &Window,
+        location: Point2D<i32>,
+    ) -> crate::Result<()> {
+        window.inner_window()?.warp_pointer(self, location)
+    }
+
+    /// Replace the `MouseMove` events of windows in relative mouse mode with `MouseDelta`
+    /// events.
+    fn translate_relative_motion(&self, evs: &mut SmallVec<[Event; 2]>) {
+        let mut i = 0;
+        while i < evs.len() {
+            let delta = match *evs[i].data() {
+                EventData::MouseMove(loc, _, _) => evs[i].window().relative_motion(self, loc),
+                _ => Ok(None),
+            };
+
+            match delta {
+                // moving the pointer back to the center shows up as motion that went nowhere
+                Ok(Some(delta)) if delta == Vector2D::zero() => {
+                    evs.remove(i);
+                }
+                Ok(Some(delta)) => {
+                    evs[i] = Event::new(evs[i].window(), EventData::MouseDelta(delta));
+                    i += 1;
+                }
+                Ok(None) => i += 1,
+                Err(e) => {
+                    log::error!("Unable to track relative mouse motion: {}", e);
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    /// Record that a window has been closed, and tell whether or not the application
+    /// should quit as a result.
+    pub(crate) fn window_closed(&self, window: &Window) -> crate::Result<bool> {
+        if !window.is_top_level()? {
+            return Ok(false);
+        }
+
+        // closed windows count as open until destroyed, so check if this is the only one left
+        let otl = self.0.open_top_levels.lock();
+        let is_last = otl.len() == 1 && otl.contains(&window.id());
+
+        Ok(match self.quit_policy() {
+            QuitPolicy::FirstTopLevelClosed => true,
+            QuitPolicy::LastWindowClosed => is_last,
+            QuitPolicy::Manual => false,
+        })
+    }
+
+    /// Get a reference to this Instance that does not keep it alive.
+    #[inline]
+    pub(crate) fn downgrade(&self) -> WeakInstance {
+        WeakInstance(Arc::downgrade(&self.0))
+    }
+
+    /// Remove a window that has been destroyed from the records of this instance.
+    pub(crate) fn window_destroyed(&self, window: &Window) {
+        self.0.open_top_levels.lock().remove(&window.id());
+        self.set_focused(window, false);
+        self.0
+            .window_mappings
+            .lock()
+            .retain(|_, w| w.id() != window.id());
+    }
+
+    /// Get the window that currently has the keyboard focus, if it belongs to this
+    /// instance.
+    #[inline]
+    pub fn focused_window(&self) -> Option<Window> {
+        let id = (*self.0.focused_window.lock())?;
+        self.0
+            .window_mappings
+            .lock()
+            .values()
+            .find(|w| w.id() == id)
+            .cloned()
+    }
+
+    /// Record that a window has gained or lost the keyboard focus.
+    #[inline]
+    pub(crate) fn set_focused(&self, window: &Window, focused: bool) {
+        let mut fw = self.0.focused_window.lock();
+        if focused {
+            *fw = Some(window.id());
+        } else if *fw == Some(window.id()) {
+            *fw = None;
+        }
+    }
+
+    /// Tell if the specified window has the keyboard focus.
+    #[inline]
+    pub(crate) fn is_focused(&self, window: &Window) -> bool {
+        *self.0.focused_window.lock() == Some(window.id())
+    }
+
+    /// Set the clock used to timestamp events and to measure time-based thresholds.
+    ///
+    /// By default, the Instance follows the system's monotonic clock. Tests can use a
+    /// `ManualClock` to control exactly how much time passes between events.
+    #[inline]
+    pub fn set_clock<C: Clock + 'static>(&self, clock: C) {
+        *self.0.clock.write() = Arc::new(clock);
+    }
+
+    /// Get the clock used by this instance.
+    #[inline]
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.0.clock.read().clone()
+    }
+
+    /// Get the current time, according to the clock used by this instance.
+    #[inline]
+    pub fn now(&self) -> Duration {
+        self.0.clock.read().now()
+    }
+
+    /// Get a snapshot of the statistics collected by the event loop.
+    ///
+    /// This includes the depth of the event queue, how many events of each type have been
+    /// translated from the backend, how many events were dropped because their window
+    /// did not receive them, and how long event handlers and paint operations took.
+    #[inline]
+    pub fn stats(&self) -> InstanceStats {
+        let depth = self.0.event_queue.lock().len();
+        let mut stats = self.0.stats.lock().clone();
+        stats.set_queue_depth(depth);
+        stats
+    }
+
+    #[inline]
+    pub(crate) fn record_handler_time(&self, window_id: usize, elapsed: Duration) {
+        self.0.stats.lock().record_handler_time(window_id, elapsed);
+    }
+
+    #[inline]
+    pub(crate) fn record_paint_time(&self, elapsed: Duration) {
+        self.0.stats.lock().record_paint_time(elapsed);
     }
 
     /// Get the next event.
     #[inline]
     pub fn next_event(&self) -> crate::Result<Event> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("next_event").entered();
+
         #[inline]
         fn hold_for_events(this: &Instance) -> crate::Result<SmallVec<[Event; 2]>> {
             cfg_if::cfg_if! {
                 if #[cfg(target_os = "linux")] {
-                    Event::from_flutter(this, flutterbug::Event::next(&this.0.connection)?)
+                    this.flutterbug_hold_for_events()
                 } else if #[cfg(windows)] {
                     this.porcupine_hold_for_events()
                 } else {
@@ -197,13 +588,28 @@ impl Instance {
                 let mut ne: Option<Event> = None;
                 while ne.is_none() {
                     let mut new_evs = hold_for_events(self)?;
-                    let mut drain = new_evs
-                        .drain(..)
-                        .filter(|e| e.window().receives_event(&e.ty()));
+                    self.translate_relative_motion(&mut new_evs);
+                    let mut stats = self.0.stats.lock();
+                    new_evs.iter().for_each(|e| stats.record_translated(e.ty()));
+                    mem::drop(stats);
+
+                    // gestures are made up by us, so they aren't counted as translated
+                    self.synthesize_gestures(&mut new_evs);
+                    let total = new_evs.len();
+                    new_evs.retain(|e| e.window().receives_event(&e.ty()));
+                    let dropped = total - new_evs.len();
+
+                    let mut drain = new_evs.drain(..);
                     let mut evq = self.0.event_queue.lock();
 
                     ne = drain.next();
                     drain.for_each(|ev| evq.push_back(ev));
+                    let depth = evq.len();
+                    mem::drop(evq);
+
+                    let mut stats = self.0.stats.lock();
+                    stats.record_dropped(dropped as u64);
+                    stats.set_queue_depth(depth);
                 }
                 Ok(ne.unwrap())
             }
@@ -211,25 +617,133 @@ impl Instance {
     }
 }
 
+// atoms that are interned as soon as the connection is opened
+#[cfg(target_os = "linux")]
+const PRELOADED_ATOMS: &[&str] = &[
+    "WM_PROTOCOLS",
+    "WM_DELETE_WINDOW",
+    "WM_TAKE_FOCUS",
+    "_NET_WM_PING",
+    "_NET_WM_STATE",
+];
+
+/// The window manager protocols that every top-level window takes part in.
+#[cfg(target_os = "linux")]
+pub(crate) const WM_PROTOCOLS: &[&str] = &["WM_DELETE_WINDOW", "WM_TAKE_FOCUS", "_NET_WM_PING"];
+
+// XEventsQueued mode, from Xlib.h; the x11 crate doesn't define it
 #[cfg(target_os = "linux")]
-const DELETE_WINDOW_ATOM: usize = 0;
+const QUEUED_AFTER_READING: cty::c_int = 1;
 
+// XKB constants, from XKB.h; the x11 crate doesn't define these either
 #[cfg(target_os = "linux")]
-use flutterbug::x11::xlib::Window as WindowID;
+const XKB_USE_CORE_KBD: cty::c_uint = 0x0100;
+#[cfg(target_os = "linux")]
+const XKB_GROUP_NAMES_MASK: cty::c_uint = 1 << 12;
+#[cfg(target_os = "linux")]
+const XKB_NEW_KEYBOARD_NOTIFY: cty::c_int = 0;
+#[cfg(target_os = "linux")]
+const XKB_STATE_NOTIFY: cty::c_int = 2;
+#[cfg(target_os = "linux")]
+const XKB_NEW_KEYBOARD_NOTIFY_MASK: cty::c_ulong = 1 << 0;
+#[cfg(target_os = "linux")]
+const XKB_GROUP_STATE_MASK: cty::c_ulong = 1 << 4;
+
+// ask XKB to tell us when the keyboard or its active layout changes. returns the event
+// type XKB events use, and the current layout group
+#[cfg(target_os = "linux")]
+fn xkb_select_events(dpy: *mut xlib::Display) -> (Option<cty::c_int>, Option<u32>) {
+    let (mut opcode, mut event_base, mut error_base) = (0, 0, 0);
+    let (mut major, mut minor) = (1, 0);
+    let present = unsafe {
+        xlib::XkbQueryExtension(
+            dpy,
+            &mut opcode,
+            &mut event_base,
+            &mut error_base,
+            &mut major,
+            &mut minor,
+        )
+    };
+    if present == xlib::False {
+        return (None, None);
+    }
+
+    // only group changes are interesting, every modifier press is a state change too
+    unsafe {
+        let mask = XKB_NEW_KEYBOARD_NOTIFY_MASK;
+        xlib::XkbSelectEvents(dpy, XKB_USE_CORE_KBD, mask, mask);
+        xlib::XkbSelectEventDetails(
+            dpy,
+            XKB_USE_CORE_KBD,
+            XKB_STATE_NOTIFY as _,
+            XKB_GROUP_STATE_MASK,
+            XKB_GROUP_STATE_MASK,
+        );
+    }
+
+    let mut state = mem::MaybeUninit::<xlib::XkbStateRec>::uninit();
+    let group = if unsafe { xlib::XkbGetState(dpy, XKB_USE_CORE_KBD, state.as_mut_ptr()) } == 0 {
+        Some(unsafe { state.assume_init() }.group as u32)
+    } else {
+        None
+    };
+    (Some(event_base), group)
+}
+
+#[cfg(target_os = "linux")]
+use crate::{signal::SignalPipe, Signal};
+#[cfg(target_os = "linux")]
+use flutterbug::x11::xlib::{self, Window as WindowID};
 
 #[cfg(target_os = "linux")]
 impl Instance {
     /// Create the flutterbug instance of the Beetle GUI factory.
+    // the X11 handles aren't thread safe, but the Arc is still needed for weak handles
+    #[allow(clippy::arc_with_non_send_sync)]
     fn flutterbug_new() -> crate::Result<Instance> {
         use flutterbug::{prelude::*, Display};
 
         let dpy = Display::new()?;
+        crate::xerror::install(dpy.raw()?.as_ptr());
+
+        // ask the server to stop sending a release before every repeated press
+        let mut detectable_repeat = xlib::False;
+        unsafe {
+            xlib::XkbSetDetectableAutoRepeat(
+                dpy.raw()?.as_ptr(),
+                xlib::True,
+                &mut detectable_repeat,
+            )
+        };
+        let detectable_repeat = detectable_repeat != xlib::False;
+        let (xkb_event_base, keyboard_group) = xkb_select_events(dpy.raw()?.as_ptr());
+
+        let atoms = PRELOADED_ATOMS
+            .iter()
+            .map(|name| Ok((*name, dpy.internal_atom(name, false)?)))
+            .collect::<crate::Result<HashMap<_, _>>>()?;
 
         Ok(Self(Arc::new(InstanceInternal {
             event_queue: Mutex::new(VecDeque::new()),
+            stats: Mutex::new(InstanceStats::default()),
+            clock: RwLock::new(default_clock()),
+            quit_policy: Mutex::new(QuitPolicy::default()),
+            open_top_levels: Mutex::new(HashSet::new()),
+            focused_window: Mutex::new(None),
+            key_repeat_filter: Mutex::new(false),
+            gestures: Mutex::new(GestureTracker::default()),
             window_mappings: Mutex::new(HashMap::new()),
-            atoms: [dpy.internal_atom("WM_DELETE_WINDOW", false)?],
+            atoms: Mutex::new(atoms),
             im: dpy.input_method()?,
+            signal_pipe: Mutex::new(None),
+            pending_signals: Mutex::new(SmallVec::new()),
+            keyboard_group: Mutex::new(keyboard_group),
+            xkb_event_base,
+            modifier_map: Mutex::new(None),
+            pressed_keys: Mutex::new(HashSet::new()),
+            font_cursors: Mutex::new(HashMap::new()),
+            detectable_repeat,
             connection: dpy,
         })))
     }
@@ -247,6 +761,415 @@ impl Instance {
         &self.0.connection
     }
 
+    /// Get the raw Xlib display pointer, for operations that Flutterbug does not cover.
+    #[inline]
+    pub(crate) fn raw_display(&self) -> crate::Result<*mut xlib::Display> {
+        Ok(self.0.connection.raw()?.as_ptr())
+    }
+
+    /// Translate POSIX signals into events.
+    ///
+    /// Normally, signals like SIGINT and SIGTERM kill the process immediately, since
+    /// `next_event` spends most of its time blocked inside of Xlib. After calling this
+    /// function, the specified signals instead cause `next_event` to wake up and return
+    /// a `Quit` event. `is_exit_event()` returns true for this event, and the `Signal`
+    /// that caused it is available as the event's first argument.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use beetle::{Instance, Signal};
+    ///
+    /// # fn main() -> beetle::Result<()> {
+    /// let instance = Instance::new()?;
+    /// instance.handle_signals(&[Signal::Interrupt, Signal::Terminate])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn handle_signals(&self, signals: &[Signal]) -> crate::Result<()> {
+        let mut sp = self.0.signal_pipe.lock();
+        if sp.is_none() {
+            *sp = Some(SignalPipe::new()?);
+        }
+
+        sp.as_ref().unwrap().install(signals)
+    }
+
+    /// Get the next batch of events from the X server or the signal pipe.
+    fn flutterbug_hold_for_events(&self) -> crate::Result<SmallVec<[Event; 2]>> {
+        // errors caused by earlier calls are reported before we block
+        crate::xerror::check()?;
+        let mut evs = self.flutterbug_wait_for_events()?;
+        self.flutterbug_coalesce_scroll(&mut evs)?;
+        crate::xerror::check()?;
+        Ok(evs)
+    }
+
+    /// Merge any wheel clicks that are already waiting in Xlib's queue into the last
+    /// scroll event of the batch.
+    fn flutterbug_coalesce_scroll(&self, evs: &mut SmallVec<[Event; 2]>) -> crate::Result<()> {
+        let ex_id = match evs.last() {
+            Some(ev) if ev.ty() == crate::EventType::Scroll => {
+                ev.window().inner_window()?.inner_flutter_window().window()
+            }
+            _ => return Ok(()),
+        };
+
+        let dpy = self.raw_display()?;
+        while unsafe { xlib::XEventsQueued(dpy, QUEUED_AFTER_READING) } > 0 {
+            // only take the next event if it's a wheel click on the same window
+            let mut xev = mem::MaybeUninit::<xlib::XEvent>::uninit();
+            let xev = unsafe {
+                xlib::XPeekEvent(dpy, xev.as_mut_ptr());
+                xev.assume_init()
+            };
+
+            let ty = xev.get_type();
+            if ty != xlib::ButtonPress && ty != xlib::ButtonRelease {
+                break;
+            }
+
+            let bev = unsafe { xev.button };
+            if bev.window != ex_id || !(4..=7).contains(&bev.button) {
+                break;
+            }
+
+            let new_evs = Event::from_flutter(self, flutterbug::Event::next(&self.0.connection)?)?;
+            for ev in new_evs {
+                let merged = evs.last_mut().map(|l| l.merge(&ev)).unwrap_or(false);
+                if !merged {
+                    evs.push(ev);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Take the next event from the X server, blocking if there isn't one.
+    ///
+    /// Flutterbug doesn't know about XKB events, so those are picked out here first.
+    fn flutterbug_next_event(&self) -> crate::Result<SmallVec<[Event; 2]>> {
+        if let Some(xkb_event_base) = self.0.xkb_event_base {
+            let dpy = self.raw_display()?;
+            let mut xev = mem::MaybeUninit::<xlib::XEvent>::uninit();
+            let mut xev = unsafe {
+                xlib::XPeekEvent(dpy, xev.as_mut_ptr());
+                xev.assume_init()
+            };
+
+            if xev.get_type() == xkb_event_base {
+                unsafe { xlib::XNextEvent(dpy, &mut xev) };
+                return Ok(self.flutterbug_xkb_events(&xev));
+            }
+        }
+
+        Event::from_flutter(self, flutterbug::Event::next(&self.0.connection)?)
+    }
+
+    /// Create a `KeymapChanged` event if an XKB event means that the layout has changed.
+    fn flutterbug_xkb_events(&self, xev: &xlib::XEvent) -> SmallVec<[Event; 2]> {
+        let mut evs = SmallVec::new();
+        let any = unsafe { &*(xev as *const xlib::XEvent as *const xlib::XkbAnyEvent) };
+        let changed = match any.xkb_type {
+            XKB_NEW_KEYBOARD_NOTIFY => true,
+            XKB_STATE_NOTIFY => {
+                let state =
+                    unsafe { &*(xev as *const xlib::XEvent as *const xlib::XkbStateNotifyEvent) };
+                self.flutterbug_update_keyboard_group(state.group as u32)
+            }
+            _ => false,
+        };
+
+        if changed {
+            if let Some(target) = self
+                .focused_window()
+                .or_else(|| self.flutterbug_any_top_level_window())
+            {
+                evs.push(Event::new(&target, EventData::KeymapChanged));
+            }
+        }
+        evs
+    }
+
+    /// Wait for the next batch of events from the X server or the signal pipe.
+    fn flutterbug_wait_for_events(&self) -> crate::Result<SmallVec<[Event; 2]>> {
+        let signal_fd = self.0.signal_pipe.lock().as_ref().map(|sp| sp.read_fd());
+
+        // if we aren't handling signals, we can just block inside of Xlib
+        let signal_fd = match signal_fd {
+            Some(fd) => fd,
+            None => return self.flutterbug_next_event(),
+        };
+
+        let dpy = self.raw_display()?;
+        loop {
+            // a dead connection polls as readable forever
+            if crate::xerror::connection_lost() {
+                return Err(crate::Error::ConnectionLost);
+            }
+
+            // Xlib may have already read events into its own queue
+            if unsafe { xlib::XPending(dpy) } > 0 {
+                return self.flutterbug_next_event();
+            }
+
+            let mut fds = [
+                libc::pollfd {
+                    fd: unsafe { xlib::XConnectionNumber(dpy) },
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: signal_fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+
+            // Note: if poll() is interrupted by a signal, it returns EINTR. The next
+            //       iteration will pick the signal up from the pipe.
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as _, -1) } <= 0 {
+                continue;
+            }
+
+            if fds[1].revents & libc::POLLIN != 0 {
+                let signals = self
+                    .0
+                    .signal_pipe
+                    .lock()
+                    .as_ref()
+                    .map(|sp| sp.drain())
+                    .unwrap_or_default();
+                let evs = self.signal_events(&signals);
+                if !evs.is_empty() {
+                    return Ok(evs);
+                }
+            }
+        }
+    }
+
+    /// Create the events corresponding to a set of received signals. Events need a window,
+    /// so if there aren't any, the signals are held onto until one is created.
+    fn signal_events(&self, signals: &[Signal]) -> SmallVec<[Event; 2]> {
+        let mut evs = SmallVec::new();
+        let mut pending = self.0.pending_signals.lock();
+        pending.extend_from_slice(signals);
+        if pending.is_empty() {
+            return evs;
+        }
+
+        let target = match self.flutterbug_any_top_level_window() {
+            Some(w) => w,
+            None => {
+                log::debug!(
+                    "Holding signals {:?} until there is a window to target",
+                    *pending
+                );
+                return evs;
+            }
+        };
+
+        let signals = mem::take(&mut *pending);
+        mem::drop(pending);
+        if let Some(signal) = signals.first() {
+            log::debug!("Translating signal {:?} into a quit event", signal);
+            let mut quit_ev = Event::new(&target, EventData::Quit);
+            quit_ev.set_is_exit_event(true);
+            quit_ev.push_argument(*signal);
+            evs.push(quit_ev);
+        }
+
+        evs
+    }
+
+    /// Get a top-level window, if there is one. If there isn't, any window is returned.
+    pub(crate) fn flutterbug_any_top_level_window(&self) -> Option<Window> {
+        let l = self.0.window_mappings.lock();
+        l.values()
+            .find(|w| w.is_top_level().unwrap_or(false))
+            .or_else(|| l.values().next())
+            .cloned()
+    }
+
+    /// Remember the keyboard group from the latest key event, and tell if it changed.
+    #[inline]
+    pub(crate) fn flutterbug_update_keyboard_group(&self, group: u32) -> bool {
+        let mut kg = self.0.keyboard_group.lock();
+        let changed = kg.is_some_and(|old| old != group);
+        *kg = Some(group);
+        changed
+    }
+
+    /// Record a key being pressed or released, and tell if a press is an autorepeat.
+    #[inline]
+    pub(crate) fn flutterbug_track_key(&self, keycode: u32, pressed: bool) -> bool {
+        let mut keys = self.0.pressed_keys.lock();
+        if pressed {
+            !keys.insert(keycode)
+        } else {
+            keys.remove(&keycode);
+            false
+        }
+    }
+
+    /// Rebuild the set of pressed keys from the server's view of the keyboard. Keys that
+    /// were released while another client had the focus would otherwise stay pressed.
+    pub(crate) fn flutterbug_sync_pressed_keys(&self) -> crate::Result<()> {
+        let dpy = self.raw_display()?;
+        let mut bits = [0 as cty::c_char; 32];
+        unsafe { xlib::XQueryKeymap(dpy, bits.as_mut_ptr()) };
+
+        // one bit per keycode, least significant bit first
+        let is_down = |keycode: &u32| (bits[*keycode as usize / 8] as u8 >> (keycode % 8)) & 1 != 0;
+        let mut keys = self.0.pressed_keys.lock();
+        keys.clear();
+        keys.extend((0..256u32).filter(is_down));
+        Ok(())
+    }
+
+    /// Tell if a key release is only there because of autorepeat. This is the case if it
+    /// is immediately followed by a press of the same key with the same timestamp.
+    pub(crate) fn flutterbug_is_fake_release(
+        &self,
+        keycode: u32,
+        time: xlib::Time,
+    ) -> crate::Result<bool> {
+        if self.0.detectable_repeat {
+            return Ok(false);
+        }
+
+        let dpy = self.raw_display()?;
+        if unsafe { xlib::XEventsQueued(dpy, QUEUED_AFTER_READING) } == 0 {
+            return Ok(false);
+        }
+
+        let mut xev = mem::MaybeUninit::<xlib::XEvent>::uninit();
+        let xev = unsafe {
+            xlib::XPeekEvent(dpy, xev.as_mut_ptr());
+            xev.assume_init()
+        };
+
+        if xev.get_type() != xlib::KeyPress {
+            return Ok(false);
+        }
+        let kev = unsafe { xev.key };
+        Ok(kev.keycode == keycode && kev.time == time)
+    }
+
+    /// Decode an X11 state mask into the modifier and lock keys that it describes.
+    pub(crate) fn flutterbug_modifiers(&self, state: cty::c_uint) -> crate::Result<Modifiers> {
+        let mut map = self.0.modifier_map.lock();
+        if map.is_none() {
+            *map = Some(self.flutterbug_modifier_map()?);
+        }
+
+        Ok(map
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(i, _)| state & (1 << i) != 0)
+            .fold(Modifiers::empty(), |mods, (_, m)| mods | *m))
+    }
+
+    /// Forget the modifier mapping, after it has been changed.
+    #[inline]
+    pub(crate) fn flutterbug_invalidate_modifiers(&self) {
+        *self.0.modifier_map.lock() = None;
+    }
+
+    // figure out which modifiers each of the eight modifier bits stand for
+    //
+    // Shift, Lock and Control are fixed, but the meaning of Mod1 through Mod5 depends on
+    // which keys are assigned to them
+    fn flutterbug_modifier_map(&self) -> crate::Result<[Modifiers; 8]> {
+        let mut map = [Modifiers::empty(); 8];
+        map[0] = Modifiers::SHIFT;
+        map[1] = Modifiers::CAPS_LOCK;
+        map[2] = Modifiers::CTRL;
+
+        let dpy = self.raw_display()?;
+        let modmap = unsafe { xlib::XGetModifierMapping(dpy) };
+        if modmap.is_null() {
+            return Err(crate::Error::StaticMsg(
+                "Unable to get the modifier mapping",
+            ));
+        }
+
+        let per_mod = unsafe { (*modmap).max_keypermod } as usize;
+        let keycodes = unsafe { core::slice::from_raw_parts((*modmap).modifiermap, 8 * per_mod) };
+        for (i, mods) in map.iter_mut().enumerate().skip(3) {
+            for &keycode in keycodes[i * per_mod..(i + 1) * per_mod].iter() {
+                if keycode == 0 {
+                    continue;
+                }
+
+                let ks = unsafe { xlib::XkbKeycodeToKeysym(dpy, keycode, 0, 0) };
+                *mods |= match ks {
+                    0xffe7 | 0xffe8 => Modifiers::META,  // XK_Meta_L, XK_Meta_R
+                    0xffe9 | 0xffea => Modifiers::ALT,   // XK_Alt_L, XK_Alt_R
+                    0xffeb | 0xffec => Modifiers::SUPER, // XK_Super_L, XK_Super_R
+                    0xffed | 0xffee => Modifiers::HYPER, // XK_Hyper_L, XK_Hyper_R
+                    0xff7f => Modifiers::NUM_LOCK,       // XK_Num_Lock
+                    0xff14 => Modifiers::SCROLL_LOCK,    // XK_Scroll_Lock
+                    // XK_ISO_Level3_Shift, XK_Mode_switch
+                    0xfe03 | 0xff7e => Modifiers::ALT_GRAPH,
+                    _ => Modifiers::empty(),
+                };
+            }
+        }
+
+        unsafe { xlib::XFreeModifiermap(modmap) };
+        Ok(map)
+    }
+
+    fn flutterbug_keyboard_layout(&self) -> crate::Result<KeyboardLayout> {
+        let dpy = self.raw_display()?;
+
+        let mut state = mem::MaybeUninit::<xlib::XkbStateRec>::uninit();
+        if unsafe { xlib::XkbGetState(dpy, XKB_USE_CORE_KBD, state.as_mut_ptr()) } != 0 {
+            return Err(crate::Error::StaticMsg(
+                "Unable to get the XKB keyboard state",
+            ));
+        }
+        let group = unsafe { state.assume_init() }.group;
+
+        // the name of the layout is the name of the active group
+        let desc = unsafe { xlib::XkbAllocKeyboard() };
+        if desc.is_null() {
+            return Err(crate::Error::StaticMsg(
+                "Unable to allocate an XKB keyboard",
+            ));
+        }
+        let name = unsafe {
+            if xlib::XkbGetNames(dpy, XKB_GROUP_NAMES_MASK, desc) == 0 && !(*desc).names.is_null() {
+                atom_name(dpy, (*(*desc).names).groups[group as usize])
+            } else {
+                String::new()
+            }
+        };
+        unsafe { xlib::XkbFreeKeyboard(desc, 0, xlib::True) };
+
+        // X11 key codes are evdev key codes plus 8
+        let labels = (8..=255u32)
+            .filter_map(|keycode| {
+                let physical = PhysicalKey::from_evdev(keycode - 8);
+                if physical == PhysicalKey::Unknown {
+                    return None;
+                }
+
+                let ks = unsafe { xlib::XkbKeycodeToKeysym(dpy, keycode as _, group as _, 0) };
+                if ks == 0 {
+                    None
+                } else {
+                    Some((physical, KeyType::from_keysym(ks)))
+                }
+            })
+            .collect();
+
+        Ok(KeyboardLayout::new(name, labels))
+    }
+
     #[inline]
     pub(crate) fn im(&self) -> &flutterbug::InputMethod {
         &self.0.im
@@ -259,29 +1182,92 @@ impl Instance {
         l.get(&ex_id).cloned()
     }
 
+    /// Get a cursor from the standard cursor font, creating it if it hasn't been already.
+    /// When Xcursor is available, Xlib loads these from the user's cursor theme.
+    pub(crate) fn flutterbug_font_cursor(&self, shape: cty::c_uint) -> crate::Result<xlib::Cursor> {
+        let mut cursors = self.0.font_cursors.lock();
+        match cursors.get(&shape) {
+            Some(cursor) => Ok(*cursor),
+            None => {
+                let cursor = unsafe { xlib::XCreateFontCursor(self.raw_display()?, shape) };
+                cursors.insert(shape, cursor);
+                Ok(cursor)
+            }
+        }
+    }
+
+    /// Read a window property that holds a list of atoms, such as `_NET_WM_STATE`. If the
+    /// property doesn't exist, the list is empty.
+    pub(crate) fn flutterbug_atom_property(
+        &self,
+        window: WindowID,
+        property: &'static str,
+    ) -> crate::Result<SmallVec<[xlib::Atom; 4]>> {
+        let mut actual_type: xlib::Atom = 0;
+        let mut actual_format: cty::c_int = 0;
+        let mut nitems: cty::c_ulong = 0;
+        let mut bytes_after: cty::c_ulong = 0;
+        let mut prop: *mut cty::c_uchar = core::ptr::null_mut();
+
+        let res = unsafe {
+            xlib::XGetWindowProperty(
+                self.raw_display()?,
+                window,
+                self.atom(property)?,
+                0,
+                1024,
+                xlib::False,
+                xlib::XA_ATOM,
+                &mut actual_type,
+                &mut actual_format,
+                &mut nitems,
+                &mut bytes_after,
+                &mut prop,
+            )
+        };
+
+        if res != xlib::Success as cty::c_int || prop.is_null() {
+            return Ok(SmallVec::new());
+        }
+
+        // 32-bit format properties are stored as an array of longs
+        let atoms = unsafe {
+            core::slice::from_raw_parts(prop as *const xlib::Atom, nitems as usize)
+                .iter()
+                .copied()
+                .collect()
+        };
+        unsafe { xlib::XFree(prop as *mut _) };
+        Ok(atoms)
+    }
+
+    /// Get the atom with the specified name, interning it if it hasn't been already.
     #[inline]
-    pub(crate) fn delete_window_atom(&self) -> flutterbug::Atom {
-        self.0.atoms[DELETE_WINDOW_ATOM]
+    pub(crate) fn atom(&self, name: &'static str) -> crate::Result<flutterbug::Atom> {
+        let mut atoms = self.0.atoms.lock();
+        match atoms.get(name) {
+            Some(atom) => Ok(*atom),
+            None => {
+                let atom = self.0.connection.internal_atom(name, false)?;
+                atoms.insert(name, atom);
+                Ok(atom)
+            }
+        }
     }
 
     #[inline]
-    fn flutterbug_create_window(
-        &self,
-        parent: Option<&Window>,
-        text: String,
-        bounds: Rect<u32>,
-        background: Option<Texture>,
-        is_top_level: bool,
-    ) -> crate::Result<Window> {
-        let cw = crate::WindowInternal::new(self, parent, text, bounds, background, is_top_level)?;
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn flutterbug_create_window(&self, builder: WindowBuilder) -> crate::Result<Window> {
+        let cw = crate::WindowInternal::new(self, builder)?;
         let id = cw.id();
         let ex_id = cw.inner_flutter_window().window();
 
         let w = Window::from_raw(
             Arc::new(RwLock::new(cw)),
             Arc::new(Mutex::new(HashSet::new())),
+            Arc::new(Mutex::new(WindowStatus::default())),
             id,
-            self.clone(),
+            self.downgrade(),
             None,
         );
         self.flutterbug_add_window(ex_id, &w);
@@ -290,6 +1276,25 @@ impl Instance {
     }
 }
 
+// get the name of an atom
+#[cfg(target_os = "linux")]
+unsafe fn atom_name(dpy: *mut xlib::Display, atom: xlib::Atom) -> String {
+    if atom == 0 {
+        return String::new();
+    }
+
+    let ptr = xlib::XGetAtomName(dpy, atom);
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    let len = (0..).take_while(|&i| *ptr.add(i) != 0).count();
+    let name =
+        String::from_utf8_lossy(core::slice::from_raw_parts(ptr as *const u8, len)).into_owned();
+    xlib::XFree(ptr as *mut _);
+    name
+}
+
 #[cfg(windows)]
 use porcupine::HWND;
 
@@ -303,21 +1308,21 @@ impl Instance {
 
         Ok(Self(Arc::new(InstanceInternal {
             event_queue: Mutex::new(VecDeque::new()),
+            stats: Mutex::new(InstanceStats::default()),
+            clock: RwLock::new(default_clock()),
+            quit_policy: Mutex::new(QuitPolicy::default()),
+            open_top_levels: Mutex::new(HashSet::new()),
+            focused_window: Mutex::new(None),
+            key_repeat_filter: Mutex::new(false),
+            gestures: Mutex::new(GestureTracker::default()),
             window_mappings: Mutex::new(HashMap::new()),
             next_events: Mutex::new(SmallVec::new()),
         })))
     }
 
     #[inline]
-    fn porcupine_create_window(
-        &self,
-        parent: Option<&Window>,
-        text: String,
-        bounds: Rect<u32>,
-        background: Option<Texture>,
-        is_top_level: bool,
-    ) -> crate::Result<Window> {
-        let cw = crate::WindowInternal::new(self, parent, text, bounds, background, is_top_level)?;
+    fn porcupine_create_window(&self, builder: WindowBuilder) -> crate::Result<Window> {
+        let cw = crate::WindowInternal::new(self, builder)?;
         let id = cw.id();
         // hashmap can only store the usize
         let ex_id = cw.inner_porc_window().hwnd().as_ptr() as *const () as usize;
@@ -325,8 +1330,9 @@ impl Instance {
         let w = Window::from_raw(
             Arc::new(RwLock::new(cw)),
             Arc::new(Mutex::new(HashSet::new())),
+            Arc::new(Mutex::new(WindowStatus::default())),
             id,
-            self.clone(),
+            self.downgrade(),
             None,
         );
 
@@ -356,6 +1362,32 @@ impl Instance {
         l.push(ne);
     }
 
+    fn porcupine_keyboard_layout(&self) -> crate::Result<KeyboardLayout> {
+        use porcupine::winapi::um::winuser::{
+            GetKeyboardLayoutNameA, MapVirtualKeyA, KL_NAMELENGTH, MAPVK_VSC_TO_VK,
+        };
+
+        let mut name = [0u8; KL_NAMELENGTH as usize];
+        unsafe { GetKeyboardLayoutNameA(name.as_mut_ptr() as *mut _) };
+        let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+        let name = String::from_utf8_lossy(&name[..len]).into_owned();
+
+        // only the unprefixed scan codes can be mapped to virtual keys
+        let labels = (1..0x59u32)
+            .filter_map(|sc| {
+                let physical = PhysicalKey::from_scancode(sc, false);
+                let vk = unsafe { MapVirtualKeyA(sc, MAPVK_VSC_TO_VK) };
+                if physical == PhysicalKey::Unknown || vk == 0 {
+                    None
+                } else {
+                    Some((physical, KeyType::from_vk(vk as usize)))
+                }
+            })
+            .collect();
+
+        Ok(KeyboardLayout::new(name, labels))
+    }
+
     #[inline]
     pub(crate) fn porcupine_hold_for_events(&self) -> crate::Result<SmallVec<[Event; 2]>> {
         // run a single iteration of the message loop
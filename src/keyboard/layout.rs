@@ -0,0 +1,100 @@
+/* -----------------------------------------------------------------------------------
+ * src/keyboard/layout.rs - A snapshot of the active keyboard layout.
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+use super::{KeyType, PhysicalKey};
+use alloc::string::String;
+use hashbrown::HashMap;
+
+/// A snapshot of the keyboard layout that is currently active.
+///
+/// This can be used to find out what is printed on a physical key, e.g. to display
+/// keyboard shortcuts that are bound to physical keys. The snapshot doesn't update
+/// itself; a `KeymapChanged` event is emitted when the layout changes.
+///
+/// # Example
+///
+/// ```no_run
+/// use beetle::{Instance, PhysicalKey};
+///
+/// # fn main() -> beetle::Result<()> {
+/// let instance = Instance::new()?;
+/// let layout = instance.keyboard_layout()?;
+///
+/// // "Z" on an AZERTY keyboard
+/// println!("{}: move forward with {:?}", layout.name(), layout.label(PhysicalKey::KeyW));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyboardLayout {
+    name: String,
+    labels: HashMap<PhysicalKey, KeyType>,
+}
+
+impl KeyboardLayout {
+    /// Create a new keyboard layout.
+    #[inline]
+    pub fn new(name: String, labels: HashMap<PhysicalKey, KeyType>) -> Self {
+        Self { name, labels }
+    }
+
+    /// The name of the layout, as reported by the backend.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The key type that a physical key produces without any modifiers.
+    #[inline]
+    pub fn label(&self, key: PhysicalKey) -> KeyType {
+        self.labels.get(&key).copied().unwrap_or(KeyType::Unknown)
+    }
+
+    /// The key types produced by every physical key in this layout.
+    #[inline]
+    pub fn labels(&self) -> &HashMap<PhysicalKey, KeyType> {
+        &self.labels
+    }
+}
@@ -43,10 +43,21 @@
  * ----------------------------------------------------------------------------------
  */
 
+use crate::ButtonState;
 use core::convert::TryInto;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod layout;
+mod modifiers;
+mod physical;
+pub use layout::*;
+pub use modifiers::*;
+pub use physical::*;
 
 /// The types of keys that can be depressed on the keyboard.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum KeyType {
     N0,
     N1,
@@ -247,27 +258,42 @@ pub enum KeyType {
     Down,
     Left,
     Right,
+    VolumeUp,
+    VolumeDown,
+    VolumeMute,
+    MediaPlayPause,
+    MediaPause,
+    MediaStop,
+    MediaPrevious,
+    MediaNext,
+    BrowserBack,
+    BrowserForward,
+    BrowserRefresh,
+    BrowserStop,
+    BrowserSearch,
+    BrowserFavorites,
+    BrowserHome,
+    LaunchMail,
+    LaunchCalculator,
+    Sleep,
+    PowerOff,
+    /// A key that produces a character with no dedicated key type, such as an accented
+    /// letter.
+    Char(char),
+    #[default]
     Unknown,
 }
 
-impl Default for KeyType {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 /// A key being pressed or released.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyInfo {
     ty: KeyType,
-    is_ctrl: bool,
-    is_alt: bool,
-    is_shift: bool,
-    is_alt_graph: bool,
-    is_button1: bool,
-    is_button2: bool,
-    is_button3: bool,
-    is_meta: bool,
+    physical: PhysicalKey,
+    scancode: u32,
+    modifiers: Modifiers,
+    buttons: ButtonState,
+    repeat: bool,
 }
 
 impl KeyInfo {
@@ -292,100 +318,188 @@ impl KeyInfo {
         self.ty = ki;
     }
 
+    /// Get the physical key, which doesn't depend on the keyboard layout.
+    #[inline]
+    pub fn physical_key(&self) -> PhysicalKey {
+        self.physical
+    }
+
+    /// Set the physical key.
+    #[inline]
+    pub fn set_physical_key(&mut self, physical: PhysicalKey) {
+        self.physical = physical;
+    }
+
+    /// Get the raw scan code reported by the backend. On X11, this is the key code.
+    #[inline]
+    pub fn scancode(&self) -> u32 {
+        self.scancode
+    }
+
+    /// Set the raw scan code.
+    #[inline]
+    pub fn set_scancode(&mut self, scancode: u32) {
+        self.scancode = scancode;
+    }
+
+    /// Get the modifier keys that were held down and the lock keys that were toggled on
+    /// when the key was pressed.
+    #[inline]
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Set the modifier and lock keys.
+    #[inline]
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    /// Get the mouse buttons that were held down when the key was pressed.
+    #[inline]
+    pub fn buttons(&self) -> ButtonState {
+        self.buttons
+    }
+
+    /// Set the mouse buttons that were held down.
+    #[inline]
+    pub fn set_buttons(&mut self, buttons: ButtonState) {
+        self.buttons = buttons;
+    }
+
+    /// Was this key press generated by holding the key down, rather than by pressing it?
+    ///
+    /// Release events are never repeats; a held key only produces a single release once
+    /// it is actually let go.
+    #[inline]
+    pub fn is_repeat(&self) -> bool {
+        self.repeat
+    }
+
+    /// Set whether this key press is an autorepeat.
+    #[inline]
+    pub fn set_repeat(&mut self, repeat: bool) {
+        self.repeat = repeat;
+    }
+
     /// Is the control key pressed?
     #[inline]
     pub fn ctrl(&self) -> bool {
-        self.is_ctrl
+        self.modifiers.contains(Modifiers::CTRL)
     }
 
     /// Set whether the control key is pressed.
     #[inline]
     pub fn set_ctrl(&mut self, is_ctrl: bool) {
-        self.is_ctrl = is_ctrl;
+        self.modifiers.set(Modifiers::CTRL, is_ctrl);
     }
 
     /// Is the alt key pressed?
     #[inline]
     pub fn alt(&self) -> bool {
-        self.is_alt
+        self.modifiers.contains(Modifiers::ALT)
     }
 
     /// Set whether the alt key is pressed.
     #[inline]
     pub fn set_alt(&mut self, is_alt: bool) {
-        self.is_alt = is_alt;
+        self.modifiers.set(Modifiers::ALT, is_alt);
     }
 
     /// Is the shift key pressed?
     #[inline]
     pub fn shift(&self) -> bool {
-        self.is_shift
+        self.modifiers.contains(Modifiers::SHIFT)
     }
 
     /// Set whether the shift key is pressed.
     #[inline]
     pub fn set_shift(&mut self, is_shift: bool) {
-        self.is_shift = is_shift;
+        self.modifiers.set(Modifiers::SHIFT, is_shift);
     }
 
     /// Is the alt graph key pressed?
     #[inline]
     pub fn alt_graph(&self) -> bool {
-        self.is_alt_graph
+        self.modifiers.contains(Modifiers::ALT_GRAPH)
     }
 
     /// Set whether the alt graph key is pressed.
     #[inline]
     pub fn set_alt_graph(&mut self, is_alt_graph: bool) {
-        self.is_alt_graph = is_alt_graph;
+        self.modifiers.set(Modifiers::ALT_GRAPH, is_alt_graph);
     }
 
     /// Is the first mouse button pressed?
     #[inline]
     pub fn button1(&self) -> bool {
-        self.is_button1
+        self.buttons.contains(ButtonState::BUTTON1)
     }
 
     /// Set whether the first mouse button is pressed.
     #[inline]
     pub fn set_button1(&mut self, is_button1: bool) {
-        self.is_button1 = is_button1;
+        self.buttons.set(ButtonState::BUTTON1, is_button1);
     }
 
     /// Is the second mouse button pressed?
     #[inline]
     pub fn button2(&self) -> bool {
-        self.is_button2
+        self.buttons.contains(ButtonState::BUTTON2)
     }
 
     /// Set whether the second mouse button is pressed.
     #[inline]
     pub fn set_button2(&mut self, is_button2: bool) {
-        self.is_button2 = is_button2;
+        self.buttons.set(ButtonState::BUTTON2, is_button2);
     }
 
     /// Is the third mouse button pressed?
     #[inline]
     pub fn button3(&self) -> bool {
-        self.is_button3
+        self.buttons.contains(ButtonState::BUTTON3)
     }
 
     /// Set whether the third mouse button is pressed.
     #[inline]
     pub fn set_button3(&mut self, is_button3: bool) {
-        self.is_button3 = is_button3;
+        self.buttons.set(ButtonState::BUTTON3, is_button3);
     }
 
     /// Is the meta button pressed?
     #[inline]
     pub fn meta(&self) -> bool {
-        self.is_meta
+        self.modifiers.contains(Modifiers::META)
     }
 
     /// Set whether the meta button is pressed.
     #[inline]
     pub fn set_meta(&mut self, is_meta: bool) {
-        self.is_meta = is_meta;
+        self.modifiers.set(Modifiers::META, is_meta);
+    }
+
+    /// Is the super key, also known as the Windows key, pressed?
+    #[inline]
+    pub fn super_key(&self) -> bool {
+        self.modifiers.contains(Modifiers::SUPER)
+    }
+
+    /// Set whether the super key is pressed.
+    #[inline]
+    pub fn set_super_key(&mut self, is_super: bool) {
+        self.modifiers.set(Modifiers::SUPER, is_super);
+    }
+
+    /// Is caps lock on?
+    #[inline]
+    pub fn caps_lock(&self) -> bool {
+        self.modifiers.contains(Modifiers::CAPS_LOCK)
+    }
+
+    /// Is num lock on?
+    #[inline]
+    pub fn num_lock(&self) -> bool {
+        self.modifiers.contains(Modifiers::NUM_LOCK)
     }
 }
 
@@ -396,13 +510,29 @@ impl KeyType {
     /// Convert an X11 keysym to a key type.
     #[inline]
     pub fn from_keysym(ks: flutterbug::KeySym) -> KeyType {
-        let u: usize = ks
-            .try_into()
-            .expect("Unable to convert KeySym into array index");
-        if u >= x11_keysym_table::X11_KEYSYM_TABLE.len() {
-            KeyType::Unknown
-        } else {
-            x11_keysym_table::X11_KEYSYM_TABLE[u]
+        match ks.try_into() {
+            Ok(ks) => Self::from_keysym_u32(ks),
+            Err(_) => KeyType::Unknown,
+        }
+    }
+
+    fn from_keysym_u32(ks: u32) -> KeyType {
+        let table = x11_keysym_table::X11_KEYSYM_TABLE;
+        match table.binary_search_by_key(&ks, |&(k, _)| k) {
+            Ok(i) => table[i].1,
+            Err(_) => match ks {
+                // Latin-1 keysyms are identical to their code points
+                0xa0..=0xff => core::char::from_u32(ks).map_or(KeyType::Unknown, KeyType::Char),
+                // unicode keysyms are the code point plus 0x01000000. the ones that overlap
+                // with Latin-1 are looked up as their Latin-1 counterparts
+                0x0100_0000..=0x0100_00ff => Self::from_keysym_u32(ks - 0x0100_0000),
+                0x0100_0100..=0x0110_ffff => {
+                    core::char::from_u32(ks - 0x0100_0000).map_or(KeyType::Unknown, KeyType::Char)
+                }
+                // older keysyms for other scripts, like Cyrillic and Greek
+                _ => x11_keysym_table::legacy_keysym_to_char(ks)
+                    .map_or(KeyType::Unknown, KeyType::Char),
+            },
         }
     }
 }
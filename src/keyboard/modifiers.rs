@@ -0,0 +1,83 @@
+/* -----------------------------------------------------------------------------------
+ * src/keyboard/modifiers.rs - The modifier keys and lock keys that are active.
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+bitflags::bitflags! {
+    /// The modifier keys that are held down and the lock keys that are toggled on.
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Modifiers: u16 {
+        const SHIFT = 1 << 0;
+        const CTRL = 1 << 1;
+        const ALT = 1 << 2;
+        const ALT_GRAPH = 1 << 3;
+        const META = 1 << 4;
+        /// The Super key, better known as the Windows key.
+        const SUPER = 1 << 5;
+        const HYPER = 1 << 6;
+        const CAPS_LOCK = 1 << 7;
+        const NUM_LOCK = 1 << 8;
+        const SCROLL_LOCK = 1 << 9;
+    }
+}
+
+impl Modifiers {
+    /// Get the modifiers that are used for keyboard shortcuts, ignoring the lock keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use beetle::Modifiers;
+    ///
+    /// let mods = Modifiers::CTRL | Modifiers::NUM_LOCK;
+    /// assert_eq!(mods.shortcut_modifiers(), Modifiers::CTRL);
+    /// ```
+    #[inline]
+    pub fn shortcut_modifiers(self) -> Modifiers {
+        self - (Self::CAPS_LOCK | Self::NUM_LOCK | Self::SCROLL_LOCK)
+    }
+}
@@ -0,0 +1,317 @@
+/* -----------------------------------------------------------------------------------
+ * src/keyboard/physical.rs - Layout-independent physical keys.
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A key identified by its position on the keyboard rather than by what is printed on
+/// it. This stays the same no matter which keyboard layout is active, so `KeyW` is
+/// always the key above `KeyS`, even on an AZERTY keyboard where it is labeled Z.
+///
+/// The names follow a US QWERTY keyboard.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PhysicalKey {
+    Escape,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Digit0,
+    Minus,
+    Equal,
+    Backspace,
+    Tab,
+    KeyQ,
+    KeyW,
+    KeyE,
+    KeyR,
+    KeyT,
+    KeyY,
+    KeyU,
+    KeyI,
+    KeyO,
+    KeyP,
+    BracketLeft,
+    BracketRight,
+    Enter,
+    ControlLeft,
+    KeyA,
+    KeyS,
+    KeyD,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyJ,
+    KeyK,
+    KeyL,
+    Semicolon,
+    Quote,
+    Backquote,
+    ShiftLeft,
+    Backslash,
+    KeyZ,
+    KeyX,
+    KeyC,
+    KeyV,
+    KeyB,
+    KeyN,
+    KeyM,
+    Comma,
+    Period,
+    Slash,
+    ShiftRight,
+    NumpadMultiply,
+    AltLeft,
+    Space,
+    CapsLock,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    NumLock,
+    ScrollLock,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadSubtract,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    NumpadAdd,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad0,
+    NumpadDecimal,
+    /// The extra key next to the left shift key on ISO keyboards.
+    IntlBackslash,
+    F11,
+    F12,
+    NumpadEnter,
+    ControlRight,
+    NumpadDivide,
+    PrintScreen,
+    AltRight,
+    Home,
+    ArrowUp,
+    PageUp,
+    ArrowLeft,
+    ArrowRight,
+    End,
+    ArrowDown,
+    PageDown,
+    Insert,
+    Delete,
+    NumpadEqual,
+    Pause,
+    SuperLeft,
+    SuperRight,
+    ContextMenu,
+    #[default]
+    Unknown,
+}
+
+impl PhysicalKey {
+    /// Convert a Linux evdev key code into a physical key. X11 key codes are the evdev key
+    /// codes plus 8.
+    pub fn from_evdev(code: u32) -> PhysicalKey {
+        use PhysicalKey::*;
+
+        match code {
+            1 => Escape,
+            2 => Digit1,
+            3 => Digit2,
+            4 => Digit3,
+            5 => Digit4,
+            6 => Digit5,
+            7 => Digit6,
+            8 => Digit7,
+            9 => Digit8,
+            10 => Digit9,
+            11 => Digit0,
+            12 => Minus,
+            13 => Equal,
+            14 => Backspace,
+            15 => Tab,
+            16 => KeyQ,
+            17 => KeyW,
+            18 => KeyE,
+            19 => KeyR,
+            20 => KeyT,
+            21 => KeyY,
+            22 => KeyU,
+            23 => KeyI,
+            24 => KeyO,
+            25 => KeyP,
+            26 => BracketLeft,
+            27 => BracketRight,
+            28 => Enter,
+            29 => ControlLeft,
+            30 => KeyA,
+            31 => KeyS,
+            32 => KeyD,
+            33 => KeyF,
+            34 => KeyG,
+            35 => KeyH,
+            36 => KeyJ,
+            37 => KeyK,
+            38 => KeyL,
+            39 => Semicolon,
+            40 => Quote,
+            41 => Backquote,
+            42 => ShiftLeft,
+            43 => Backslash,
+            44 => KeyZ,
+            45 => KeyX,
+            46 => KeyC,
+            47 => KeyV,
+            48 => KeyB,
+            49 => KeyN,
+            50 => KeyM,
+            51 => Comma,
+            52 => Period,
+            53 => Slash,
+            54 => ShiftRight,
+            55 => NumpadMultiply,
+            56 => AltLeft,
+            57 => Space,
+            58 => CapsLock,
+            59 => F1,
+            60 => F2,
+            61 => F3,
+            62 => F4,
+            63 => F5,
+            64 => F6,
+            65 => F7,
+            66 => F8,
+            67 => F9,
+            68 => F10,
+            69 => NumLock,
+            70 => ScrollLock,
+            71 => Numpad7,
+            72 => Numpad8,
+            73 => Numpad9,
+            74 => NumpadSubtract,
+            75 => Numpad4,
+            76 => Numpad5,
+            77 => Numpad6,
+            78 => NumpadAdd,
+            79 => Numpad1,
+            80 => Numpad2,
+            81 => Numpad3,
+            82 => Numpad0,
+            83 => NumpadDecimal,
+            86 => IntlBackslash,
+            87 => F11,
+            88 => F12,
+            96 => NumpadEnter,
+            97 => ControlRight,
+            98 => NumpadDivide,
+            99 => PrintScreen,
+            100 => AltRight,
+            102 => Home,
+            103 => ArrowUp,
+            104 => PageUp,
+            105 => ArrowLeft,
+            106 => ArrowRight,
+            107 => End,
+            108 => ArrowDown,
+            109 => PageDown,
+            110 => Insert,
+            111 => Delete,
+            117 => NumpadEqual,
+            119 => Pause,
+            125 => SuperLeft,
+            126 => SuperRight,
+            127 => ContextMenu,
+            _ => Unknown,
+        }
+    }
+
+    /// Convert a PC/AT scan code, as reported by Win32, into a physical key. `extended`
+    /// tells if the scan code was prefixed with 0xE0.
+    pub fn from_scancode(code: u32, extended: bool) -> PhysicalKey {
+        use PhysicalKey::*;
+
+        if !extended {
+            // the unprefixed scan codes line up with the evdev key codes
+            return Self::from_evdev(code);
+        }
+
+        match code {
+            0x1c => NumpadEnter,
+            0x1d => ControlRight,
+            0x35 => NumpadDivide,
+            0x37 => PrintScreen,
+            0x38 => AltRight,
+            0x47 => Home,
+            0x48 => ArrowUp,
+            0x49 => PageUp,
+            0x4b => ArrowLeft,
+            0x4d => ArrowRight,
+            0x4f => End,
+            0x50 => ArrowDown,
+            0x51 => PageDown,
+            0x52 => Insert,
+            0x53 => Delete,
+            0x5b => SuperLeft,
+            0x5c => SuperRight,
+            0x5d => ContextMenu,
+            _ => Unknown,
+        }
+    }
+}
@@ -43,197 +43,669 @@
  * ----------------------------------------------------------------------------------
  */
 
-
 #![cfg(target_os = "linux")]
 
 use super::KeyType::{self, *};
 
-#[allow(non_upper_case_globals)]
-const Un: KeyType = Unknown;
-
-// table of x11 keysyms to beetle keycodes
-pub const X11_KEYSYM_TABLE: [KeyType; 0xAF] = [
-    // the first 0x1F numbers are unused
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Space,            // 0x20 = XK_Space
-    ExclamationMark,  // 0x21 = XK_exclam
-    DoubleQuote,      // 0x22 = XK_quotedbl
-    NumberSign,       // 0x23 = XK_numbersign
-    Dollar,           // 0x24 = XK_dollar
-    Percent,          // 0x25 = XK_percent
-    Ampersand,        // 0x26 = XK_ampersand,
-    Quote,            // 0x27 = XK_apostrophe
-    LeftParenthesis,  // 0x28 = XK_parenleft
-    RightParenthesis, // 0x29 = XK_parenright
-    Asterisk,         // 0x2a = XK_asterisk
-    Plus,             // 0x2b = XK_plus
-    Comma,            // 0x2c = XK_comma,
-    Minus,            // 0x2d = XK_minus
-    Period,           // 0x2e = XK_period,
-    Slash,            // 0x2f = XK_Slash,
+// table of x11 keysyms to beetle keycodes, sorted by keysym so it can be binary searched
+//
+// Latin-1 and unicode keysyms that aren't in this table are handled by KeyType::from_keysym
+pub const X11_KEYSYM_TABLE: &[(u32, KeyType)] = &[
+    (0x0020, Space),            // XK_space
+    (0x0021, ExclamationMark),  // XK_exclam
+    (0x0022, DoubleQuote),      // XK_quotedbl
+    (0x0023, NumberSign),       // XK_numbersign
+    (0x0024, Dollar),           // XK_dollar
+    (0x0025, Percent),          // XK_percent
+    (0x0026, Ampersand),        // XK_ampersand
+    (0x0027, Quote),            // XK_apostrophe
+    (0x0028, LeftParenthesis),  // XK_parenleft
+    (0x0029, RightParenthesis), // XK_parenright
+    (0x002a, Asterisk),         // XK_asterisk
+    (0x002b, Plus),             // XK_plus
+    (0x002c, Comma),            // XK_comma
+    (0x002d, Minus),            // XK_minus
+    (0x002e, Period),           // XK_period
+    (0x002f, Slash),            // XK_slash
     // number keys
-    N0,
-    N1,
-    N2,
-    N3,
-    N4,
-    N5,
-    N6,
-    N7,
-    N8,
-    N9,
-    Colon,        // 0x3a = XK_colon
-    Semicolon,    // 0x3b = XK_semicolon
-    Less,         // 0x3c = XK_less
-    Equals,       // 0x3d = XK_equal
-    Greater,      // 0x3e = XK_greater,
-    QuestionMark, // 0x3f = XK_question,
-    At,           // 0x40 = XK_at,
+    (0x0030, N0),
+    (0x0031, N1),
+    (0x0032, N2),
+    (0x0033, N3),
+    (0x0034, N4),
+    (0x0035, N5),
+    (0x0036, N6),
+    (0x0037, N7),
+    (0x0038, N8),
+    (0x0039, N9),
+    (0x003a, Colon),        // XK_colon
+    (0x003b, Semicolon),    // XK_semicolon
+    (0x003c, Less),         // XK_less
+    (0x003d, Equals),       // XK_equal
+    (0x003e, Greater),      // XK_greater
+    (0x003f, QuestionMark), // XK_question
+    (0x0040, At),           // XK_at
     // the alphabet
-    A,
-    B,
-    C,
-    D,
-    E,
-    F,
-    G,
-    H,
-    I,
-    J,
-    K,
-    L,
-    M,
-    N,
-    O,
-    P,
-    Q,
-    R,
-    S,
-    T,
-    U,
-    V,
-    W,
-    X,
-    Y,
-    Z,
-    LeftBracket,  // 0x5b = XK_bracketleft,
-    BackSlash,    // 0x5c = XK_backslash
-    RightBracket, // 0x5d = XK_bracketright
-    Circumflex,   // 0x5e = XK_asciicircum
-    Underscore,   // 0x5f = XK_underscore
-    BackQuote,    // 0x60 = XK_grave
+    (0x0041, A),
+    (0x0042, B),
+    (0x0043, C),
+    (0x0044, D),
+    (0x0045, E),
+    (0x0046, F),
+    (0x0047, G),
+    (0x0048, H),
+    (0x0049, I),
+    (0x004a, J),
+    (0x004b, K),
+    (0x004c, L),
+    (0x004d, M),
+    (0x004e, N),
+    (0x004f, O),
+    (0x0050, P),
+    (0x0051, Q),
+    (0x0052, R),
+    (0x0053, S),
+    (0x0054, T),
+    (0x0055, U),
+    (0x0056, V),
+    (0x0057, W),
+    (0x0058, X),
+    (0x0059, Y),
+    (0x005a, Z),
+    (0x005b, LeftBracket),  // XK_bracketleft
+    (0x005c, BackSlash),    // XK_backslash
+    (0x005d, RightBracket), // XK_bracketright
+    (0x005e, Circumflex),   // XK_asciicircum
+    (0x005f, Underscore),   // XK_underscore
+    (0x0060, BackQuote),    // XK_grave
     // the alphabet, again
-    A,
-    B,
-    C,
-    D,
-    E,
-    F,
-    G,
-    H,
-    I,
-    J,
-    K,
-    L,
-    M,
-    N,
-    O,
-    P,
-    Q,
-    R,
-    S,
-    T,
-    U,
-    V,
-    W,
-    X,
-    Y,
-    Z,
-    LeftBrace,  // 0x7b = XK_braceleft
-    Bar,        // 0x7c = XK_bar
-    RightBrace, // 0x7d = XK_braceright
-    Tilde,      // 0x7e = XK_tidle
-    // 0x7f to 0x9f are unused
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Space,                   // 0xa0 = XK_nobreakspace
-    InvertedExclamationMark, // 0xa1 = XK_exclamdown
-    // TODO: this block contains characters that should be filled out
-    // such as shift, caps lock, etc
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    Un,
-    //        Hyphen, // 0xad = XK_hyphen
+    (0x0061, A),
+    (0x0062, B),
+    (0x0063, C),
+    (0x0064, D),
+    (0x0065, E),
+    (0x0066, F),
+    (0x0067, G),
+    (0x0068, H),
+    (0x0069, I),
+    (0x006a, J),
+    (0x006b, K),
+    (0x006c, L),
+    (0x006d, M),
+    (0x006e, N),
+    (0x006f, O),
+    (0x0070, P),
+    (0x0071, Q),
+    (0x0072, R),
+    (0x0073, S),
+    (0x0074, T),
+    (0x0075, U),
+    (0x0076, V),
+    (0x0077, W),
+    (0x0078, X),
+    (0x0079, Y),
+    (0x007a, Z),
+    (0x007b, LeftBrace),               // XK_braceleft
+    (0x007c, Bar),                     // XK_bar
+    (0x007d, RightBrace),              // XK_braceright
+    (0x007e, Tilde),                   // XK_asciitilde
+    (0x00a0, Space),                   // XK_nobreakspace
+    (0x00a1, InvertedExclamationMark), // XK_exclamdown
+    // the rest of Latin-1 becomes KeyType::Char
+    (0x20ac, EuroSign), // XK_EuroSign
+    // ISO 9995 function keys
+    (0xfe03, AltGraph), // XK_ISO_Level3_Shift
+    (0xfe20, Tab),      // XK_ISO_Left_Tab
+    // TTY function keys
+    (0xff08, BackSpace),  // XK_BackSpace
+    (0xff09, Tab),        // XK_Tab
+    (0xff0b, Clear),      // XK_Clear
+    (0xff0d, Enter),      // XK_Return
+    (0xff13, Pause),      // XK_Pause
+    (0xff14, ScrollLock), // XK_Scroll_Lock
+    (0xff1b, Escape),     // XK_Escape
+    // international and multi-key character composition
+    (0xff20, Compose),           // XK_Multi_key
+    (0xff21, Kanji),             // XK_Kanji
+    (0xff22, DontConvert),       // XK_Muhenkan
+    (0xff23, Convert),           // XK_Henkan
+    (0xff24, RomanCharacters),   // XK_Romaji
+    (0xff25, Hiragana),          // XK_Hiragana
+    (0xff26, Katakana),          // XK_Katakana
+    (0xff27, JapaneseHiragana),  // XK_Hiragana_Katakana
+    (0xff28, FullWidth),         // XK_Zenkaku
+    (0xff29, HalfWidth),         // XK_Hankaku
+    (0xff2a, InputMethodOnOff),  // XK_Zenkaku_Hankaku
+    (0xff2d, KanaLock),          // XK_Kana_Lock
+    (0xff2e, Kana),              // XK_Kana_Shift
+    (0xff30, Alphanumeric),      // XK_Eisu_toggle
+    (0xff37, CodeInput),         // XK_Codeinput
+    (0xff3d, AllCandidates),     // XK_MultipleCandidate
+    (0xff3e, PreviousCandidate), // XK_PreviousCandidate
+    // cursor control
+    (0xff50, Home),     // XK_Home
+    (0xff51, Left),     // XK_Left
+    (0xff52, Up),       // XK_Up
+    (0xff53, Right),    // XK_Right
+    (0xff54, Down),     // XK_Down
+    (0xff55, PageUp),   // XK_Prior
+    (0xff56, PageDown), // XK_Next
+    (0xff57, End),      // XK_End
+    (0xff58, Begin),    // XK_Begin
+    // misc functions
+    (0xff61, PrintScreen), // XK_Print
+    (0xff63, Insert),      // XK_Insert
+    (0xff65, Undo),        // XK_Undo
+    (0xff66, Again),       // XK_Redo
+    (0xff67, ContextMenu), // XK_Menu
+    (0xff68, Find),        // XK_Find
+    (0xff69, Cancel),      // XK_Cancel
+    (0xff6a, Help),        // XK_Help
+    (0xff7e, ModeChange),  // XK_Mode_switch
+    (0xff7f, NumLock),     // XK_Num_Lock
+    // keypad functions
+    (0xff80, Space),       // XK_KP_Space
+    (0xff89, Tab),         // XK_KP_Tab
+    (0xff8d, Enter),       // XK_KP_Enter
+    (0xff95, Home),        // XK_KP_Home
+    (0xff96, KeypadLeft),  // XK_KP_Left
+    (0xff97, KeypadUp),    // XK_KP_Up
+    (0xff98, KeypadRight), // XK_KP_Right
+    (0xff99, KeypadDown),  // XK_KP_Down
+    (0xff9a, PageUp),      // XK_KP_Prior
+    (0xff9b, PageDown),    // XK_KP_Next
+    (0xff9c, End),         // XK_KP_End
+    (0xff9d, Begin),       // XK_KP_Begin
+    (0xff9e, Insert),      // XK_KP_Insert
+    (0xff9f, Delete),      // XK_KP_Delete
+    (0xffaa, Multiply),    // XK_KP_Multiply
+    (0xffab, Add),         // XK_KP_Add
+    (0xffac, Separator),   // XK_KP_Separator
+    (0xffad, Subtract),    // XK_KP_Subtract
+    (0xffae, Decimal),     // XK_KP_Decimal
+    (0xffaf, Divide),      // XK_KP_Divide
+    (0xffb0, Numpad0),
+    (0xffb1, Numpad1),
+    (0xffb2, Numpad2),
+    (0xffb3, Numpad3),
+    (0xffb4, Numpad4),
+    (0xffb5, Numpad5),
+    (0xffb6, Numpad6),
+    (0xffb7, Numpad7),
+    (0xffb8, Numpad8),
+    (0xffb9, Numpad9),
+    (0xffbd, Equals), // XK_KP_Equal
+    // function keys
+    (0xffbe, F1),
+    (0xffbf, F2),
+    (0xffc0, F3),
+    (0xffc1, F4),
+    (0xffc2, F5),
+    (0xffc3, F6),
+    (0xffc4, F7),
+    (0xffc5, F8),
+    (0xffc6, F9),
+    (0xffc7, F10),
+    (0xffc8, F11),
+    (0xffc9, F12),
+    (0xffca, F13),
+    (0xffcb, F14),
+    (0xffcc, F15),
+    (0xffcd, F16),
+    (0xffce, F17),
+    (0xffcf, F18),
+    (0xffd0, F19),
+    (0xffd1, F20),
+    (0xffd2, F21),
+    (0xffd3, F22),
+    (0xffd4, F23),
+    (0xffd5, F24),
+    // modifiers
+    (0xffe1, LeftShift),    // XK_Shift_L
+    (0xffe2, RightShift),   // XK_Shift_R
+    (0xffe3, LeftControl),  // XK_Control_L
+    (0xffe4, RightControl), // XK_Control_R
+    (0xffe5, CapsLock),     // XK_Caps_Lock
+    (0xffe7, Meta),         // XK_Meta_L
+    (0xffe8, Meta),         // XK_Meta_R
+    (0xffe9, LeftAlt),      // XK_Alt_L
+    (0xffea, RightAlt),     // XK_Alt_R
+    (0xffeb, Windows),      // XK_Super_L
+    (0xffec, Windows),      // XK_Super_R
+    (0xffff, Delete),       // XK_Delete
+    // XFree86 vendor specific keys, mostly found on multimedia keyboards
+    (0x1008_ff11, VolumeDown),       // XF86XK_AudioLowerVolume
+    (0x1008_ff12, VolumeMute),       // XF86XK_AudioMute
+    (0x1008_ff13, VolumeUp),         // XF86XK_AudioRaiseVolume
+    (0x1008_ff14, MediaPlayPause),   // XF86XK_AudioPlay
+    (0x1008_ff15, MediaStop),        // XF86XK_AudioStop
+    (0x1008_ff16, MediaPrevious),    // XF86XK_AudioPrev
+    (0x1008_ff17, MediaNext),        // XF86XK_AudioNext
+    (0x1008_ff18, BrowserHome),      // XF86XK_HomePage
+    (0x1008_ff19, LaunchMail),       // XF86XK_Mail
+    (0x1008_ff1b, BrowserSearch),    // XF86XK_Search
+    (0x1008_ff1d, LaunchCalculator), // XF86XK_Calculator
+    (0x1008_ff26, BrowserBack),      // XF86XK_Back
+    (0x1008_ff27, BrowserForward),   // XF86XK_Forward
+    (0x1008_ff28, BrowserStop),      // XF86XK_Stop
+    (0x1008_ff29, BrowserRefresh),   // XF86XK_Refresh
+    (0x1008_ff2a, PowerOff),         // XF86XK_PowerOff
+    (0x1008_ff2f, Sleep),            // XF86XK_Sleep
+    (0x1008_ff30, BrowserFavorites), // XF86XK_Favorites
+    (0x1008_ff31, MediaPause),       // XF86XK_AudioPause
+    (0x1008_ff57, FCopy),            // XF86XK_Copy
+    (0x1008_ff58, Cut),              // XF86XK_Cut
+    (0x1008_ff6d, Paste),            // XF86XK_Paste
+];
+
+// table of legacy keysyms outside of Latin-1 to the unicode code points they stand for,
+// sorted by keysym so it can be binary searched. these predate the unicode keysyms, but
+// are still what most layouts for these scripts produce.
+//
+// blocks where the keysyms line up with unicode are handled by legacy_keysym_to_char
+const LEGACY_KEYSYM_TABLE: &[(u32, u16)] = &[
+    // Latin-2
+    (0x01a1, 0x0104), // XK_Aogonek
+    (0x01a2, 0x02d8), // XK_breve
+    (0x01a3, 0x0141), // XK_Lstroke
+    (0x01a5, 0x013d), // XK_Lcaron
+    (0x01a6, 0x015a), // XK_Sacute
+    (0x01a9, 0x0160), // XK_Scaron
+    (0x01aa, 0x015e), // XK_Scedilla
+    (0x01ab, 0x0164), // XK_Tcaron
+    (0x01ac, 0x0179), // XK_Zacute
+    (0x01ae, 0x017d), // XK_Zcaron
+    (0x01af, 0x017b), // XK_Zabovedot
+    (0x01b1, 0x0105), // XK_aogonek
+    (0x01b2, 0x02db), // XK_ogonek
+    (0x01b3, 0x0142), // XK_lstroke
+    (0x01b5, 0x013e), // XK_lcaron
+    (0x01b6, 0x015b), // XK_sacute
+    (0x01b7, 0x02c7), // XK_caron
+    (0x01b9, 0x0161), // XK_scaron
+    (0x01ba, 0x015f), // XK_scedilla
+    (0x01bb, 0x0165), // XK_tcaron
+    (0x01bc, 0x017a), // XK_zacute
+    (0x01bd, 0x02dd), // XK_doubleacute
+    (0x01be, 0x017e), // XK_zcaron
+    (0x01bf, 0x017c), // XK_zabovedot
+    (0x01c0, 0x0154), // XK_Racute
+    (0x01c3, 0x0102), // XK_Abreve
+    (0x01c5, 0x0139), // XK_Lacute
+    (0x01c6, 0x0106), // XK_Cacute
+    (0x01c8, 0x010c), // XK_Ccaron
+    (0x01ca, 0x0118), // XK_Eogonek
+    (0x01cc, 0x011a), // XK_Ecaron
+    (0x01cf, 0x010e), // XK_Dcaron
+    (0x01d0, 0x0110), // XK_Dstroke
+    (0x01d1, 0x0143), // XK_Nacute
+    (0x01d2, 0x0147), // XK_Ncaron
+    (0x01d5, 0x0150), // XK_Odoubleacute
+    (0x01d8, 0x0158), // XK_Rcaron
+    (0x01d9, 0x016e), // XK_Uring
+    (0x01db, 0x0170), // XK_Udoubleacute
+    (0x01de, 0x0162), // XK_Tcedilla
+    (0x01e0, 0x0155), // XK_racute
+    (0x01e3, 0x0103), // XK_abreve
+    (0x01e5, 0x013a), // XK_lacute
+    (0x01e6, 0x0107), // XK_cacute
+    (0x01e8, 0x010d), // XK_ccaron
+    (0x01ea, 0x0119), // XK_eogonek
+    (0x01ec, 0x011b), // XK_ecaron
+    (0x01ef, 0x010f), // XK_dcaron
+    (0x01f0, 0x0111), // XK_dstroke
+    (0x01f1, 0x0144), // XK_nacute
+    (0x01f2, 0x0148), // XK_ncaron
+    (0x01f5, 0x0151), // XK_odoubleacute
+    (0x01f8, 0x0159), // XK_rcaron
+    (0x01f9, 0x016f), // XK_uring
+    (0x01fb, 0x0171), // XK_udoubleacute
+    (0x01fe, 0x0163), // XK_tcedilla
+    (0x01ff, 0x02d9), // XK_abovedot
+    // Latin-3
+    (0x02a1, 0x0126), // XK_Hstroke
+    (0x02a6, 0x0124), // XK_Hcircumflex
+    (0x02a9, 0x0130), // XK_Iabovedot
+    (0x02ab, 0x011e), // XK_Gbreve
+    (0x02ac, 0x0134), // XK_Jcircumflex
+    (0x02b1, 0x0127), // XK_hstroke
+    (0x02b6, 0x0125), // XK_hcircumflex
+    (0x02b9, 0x0131), // XK_idotless
+    (0x02bb, 0x011f), // XK_gbreve
+    (0x02bc, 0x0135), // XK_jcircumflex
+    (0x02c5, 0x010a), // XK_Cabovedot
+    (0x02c6, 0x0108), // XK_Ccircumflex
+    (0x02d5, 0x0120), // XK_Gabovedot
+    (0x02d8, 0x011c), // XK_Gcircumflex
+    (0x02dd, 0x016c), // XK_Ubreve
+    (0x02de, 0x015c), // XK_Scircumflex
+    (0x02e5, 0x010b), // XK_cabovedot
+    (0x02e6, 0x0109), // XK_ccircumflex
+    (0x02f5, 0x0121), // XK_gabovedot
+    (0x02f8, 0x011d), // XK_gcircumflex
+    (0x02fd, 0x016d), // XK_ubreve
+    (0x02fe, 0x015d), // XK_scircumflex
+    // Latin-4
+    (0x03a2, 0x0138), // XK_kra
+    (0x03a3, 0x0156), // XK_Rcedilla
+    (0x03a5, 0x0128), // XK_Itilde
+    (0x03a6, 0x013b), // XK_Lcedilla
+    (0x03aa, 0x0112), // XK_Emacron
+    (0x03ab, 0x0122), // XK_Gcedilla
+    (0x03ac, 0x0166), // XK_Tslash
+    (0x03b3, 0x0157), // XK_rcedilla
+    (0x03b5, 0x0129), // XK_itilde
+    (0x03b6, 0x013c), // XK_lcedilla
+    (0x03ba, 0x0113), // XK_emacron
+    (0x03bb, 0x0123), // XK_gcedilla
+    (0x03bc, 0x0167), // XK_tslash
+    (0x03bd, 0x014a), // XK_ENG
+    (0x03bf, 0x014b), // XK_eng
+    (0x03c0, 0x0100), // XK_Amacron
+    (0x03c7, 0x012e), // XK_Iogonek
+    (0x03cc, 0x0116), // XK_Eabovedot
+    (0x03cf, 0x012a), // XK_Imacron
+    (0x03d1, 0x0145), // XK_Ncedilla
+    (0x03d2, 0x014c), // XK_Omacron
+    (0x03d3, 0x0136), // XK_Kcedilla
+    (0x03d9, 0x0172), // XK_Uogonek
+    (0x03dd, 0x0168), // XK_Utilde
+    (0x03de, 0x016a), // XK_Umacron
+    (0x03e0, 0x0101), // XK_amacron
+    (0x03e7, 0x012f), // XK_iogonek
+    (0x03ec, 0x0117), // XK_eabovedot
+    (0x03ef, 0x012b), // XK_imacron
+    (0x03f1, 0x0146), // XK_ncedilla
+    (0x03f2, 0x014d), // XK_omacron
+    (0x03f3, 0x0137), // XK_kcedilla
+    (0x03f9, 0x0173), // XK_uogonek
+    (0x03fd, 0x0169), // XK_utilde
+    (0x03fe, 0x016b), // XK_umacron
+    // Katakana
+    (0x04a1, 0x3002), // XK_kana_fullstop
+    (0x04a2, 0x300c), // XK_kana_openingbracket
+    (0x04a3, 0x300d), // XK_kana_closingbracket
+    (0x04a4, 0x3001), // XK_kana_comma
+    (0x04a5, 0x30fb), // XK_kana_conjunctive
+    (0x04a6, 0x30f2), // XK_kana_WO
+    (0x04a7, 0x30a1), // XK_kana_a
+    (0x04a8, 0x30a3), // XK_kana_i
+    (0x04a9, 0x30a5), // XK_kana_u
+    (0x04aa, 0x30a7), // XK_kana_e
+    (0x04ab, 0x30a9), // XK_kana_o
+    (0x04ac, 0x30e3), // XK_kana_ya
+    (0x04ad, 0x30e5), // XK_kana_yu
+    (0x04ae, 0x30e7), // XK_kana_yo
+    (0x04af, 0x30c3), // XK_kana_tsu
+    (0x04b0, 0x30fc), // XK_prolongedsound
+    (0x04b1, 0x30a2), // XK_kana_A
+    (0x04b2, 0x30a4), // XK_kana_I
+    (0x04b3, 0x30a6), // XK_kana_U
+    (0x04b4, 0x30a8), // XK_kana_E
+    (0x04b5, 0x30aa), // XK_kana_O
+    (0x04b6, 0x30ab), // XK_kana_KA
+    (0x04b7, 0x30ad), // XK_kana_KI
+    (0x04b8, 0x30af), // XK_kana_KU
+    (0x04b9, 0x30b1), // XK_kana_KE
+    (0x04ba, 0x30b3), // XK_kana_KO
+    (0x04bb, 0x30b5), // XK_kana_SA
+    (0x04bc, 0x30b7), // XK_kana_SHI
+    (0x04bd, 0x30b9), // XK_kana_SU
+    (0x04be, 0x30bb), // XK_kana_SE
+    (0x04bf, 0x30bd), // XK_kana_SO
+    (0x04c0, 0x30bf), // XK_kana_TA
+    (0x04c1, 0x30c1), // XK_kana_CHI
+    (0x04c2, 0x30c4), // XK_kana_TSU
+    (0x04c3, 0x30c6), // XK_kana_TE
+    (0x04c4, 0x30c8), // XK_kana_TO
+    (0x04c5, 0x30ca), // XK_kana_NA
+    (0x04c6, 0x30cb), // XK_kana_NI
+    (0x04c7, 0x30cc), // XK_kana_NU
+    (0x04c8, 0x30cd), // XK_kana_NE
+    (0x04c9, 0x30ce), // XK_kana_NO
+    (0x04ca, 0x30cf), // XK_kana_HA
+    (0x04cb, 0x30d2), // XK_kana_HI
+    (0x04cc, 0x30d5), // XK_kana_FU
+    (0x04cd, 0x30d8), // XK_kana_HE
+    (0x04ce, 0x30db), // XK_kana_HO
+    (0x04cf, 0x30de), // XK_kana_MA
+    (0x04d0, 0x30df), // XK_kana_MI
+    (0x04d1, 0x30e0), // XK_kana_MU
+    (0x04d2, 0x30e1), // XK_kana_ME
+    (0x04d3, 0x30e2), // XK_kana_MO
+    (0x04d4, 0x30e4), // XK_kana_YA
+    (0x04d5, 0x30e6), // XK_kana_YU
+    (0x04d6, 0x30e8), // XK_kana_YO
+    (0x04d7, 0x30e9), // XK_kana_RA
+    (0x04d8, 0x30ea), // XK_kana_RI
+    (0x04d9, 0x30eb), // XK_kana_RU
+    (0x04da, 0x30ec), // XK_kana_RE
+    (0x04db, 0x30ed), // XK_kana_RO
+    (0x04dc, 0x30ef), // XK_kana_WA
+    (0x04dd, 0x30f3), // XK_kana_N
+    (0x04de, 0x309b), // XK_voicedsound
+    (0x04df, 0x309c), // XK_semivoicedsound
+    // Arabic punctuation; the letters are handled by an offset
+    (0x05ac, 0x060c), // XK_Arabic_comma
+    (0x05bb, 0x061b), // XK_Arabic_semicolon
+    (0x05bf, 0x061f), // XK_Arabic_question_mark
+    // Cyrillic
+    (0x06a1, 0x0452), // XK_Serbian_dje
+    (0x06a2, 0x0453), // XK_Macedonia_gje
+    (0x06a3, 0x0451), // XK_Cyrillic_io
+    (0x06a4, 0x0454), // XK_Ukrainian_ie
+    (0x06a5, 0x0455), // XK_Macedonia_dse
+    (0x06a6, 0x0456), // XK_Ukrainian_i
+    (0x06a7, 0x0457), // XK_Ukrainian_yi
+    (0x06a8, 0x0458), // XK_Cyrillic_je
+    (0x06a9, 0x0459), // XK_Cyrillic_lje
+    (0x06aa, 0x045a), // XK_Cyrillic_nje
+    (0x06ab, 0x045b), // XK_Serbian_tshe
+    (0x06ac, 0x045c), // XK_Macedonia_kje
+    (0x06ad, 0x0491), // XK_Ukrainian_ghe_with_upturn
+    (0x06ae, 0x045e), // XK_Byelorussian_shortu
+    (0x06af, 0x045f), // XK_Cyrillic_dzhe
+    (0x06b0, 0x2116), // XK_numerosign
+    (0x06b1, 0x0402), // XK_Serbian_DJE
+    (0x06b2, 0x0403), // XK_Macedonia_GJE
+    (0x06b3, 0x0401), // XK_Cyrillic_IO
+    (0x06b4, 0x0404), // XK_Ukrainian_IE
+    (0x06b5, 0x0405), // XK_Macedonia_DSE
+    (0x06b6, 0x0406), // XK_Ukrainian_I
+    (0x06b7, 0x0407), // XK_Ukrainian_YI
+    (0x06b8, 0x0408), // XK_Cyrillic_JE
+    (0x06b9, 0x0409), // XK_Cyrillic_LJE
+    (0x06ba, 0x040a), // XK_Cyrillic_NJE
+    (0x06bb, 0x040b), // XK_Serbian_TSHE
+    (0x06bc, 0x040c), // XK_Macedonia_KJE
+    (0x06bd, 0x0490), // XK_Ukrainian_GHE_WITH_UPTURN
+    (0x06be, 0x040e), // XK_Byelorussian_SHORTU
+    (0x06bf, 0x040f), // XK_Cyrillic_DZHE
+    (0x06c0, 0x044e), // XK_Cyrillic_yu
+    (0x06c1, 0x0430), // XK_Cyrillic_a
+    (0x06c2, 0x0431), // XK_Cyrillic_be
+    (0x06c3, 0x0446), // XK_Cyrillic_tse
+    (0x06c4, 0x0434), // XK_Cyrillic_de
+    (0x06c5, 0x0435), // XK_Cyrillic_ie
+    (0x06c6, 0x0444), // XK_Cyrillic_ef
+    (0x06c7, 0x0433), // XK_Cyrillic_ghe
+    (0x06c8, 0x0445), // XK_Cyrillic_ha
+    (0x06c9, 0x0438), // XK_Cyrillic_i
+    (0x06ca, 0x0439), // XK_Cyrillic_shorti
+    (0x06cb, 0x043a), // XK_Cyrillic_ka
+    (0x06cc, 0x043b), // XK_Cyrillic_el
+    (0x06cd, 0x043c), // XK_Cyrillic_em
+    (0x06ce, 0x043d), // XK_Cyrillic_en
+    (0x06cf, 0x043e), // XK_Cyrillic_o
+    (0x06d0, 0x043f), // XK_Cyrillic_pe
+    (0x06d1, 0x044f), // XK_Cyrillic_ya
+    (0x06d2, 0x0440), // XK_Cyrillic_er
+    (0x06d3, 0x0441), // XK_Cyrillic_es
+    (0x06d4, 0x0442), // XK_Cyrillic_te
+    (0x06d5, 0x0443), // XK_Cyrillic_u
+    (0x06d6, 0x0436), // XK_Cyrillic_zhe
+    (0x06d7, 0x0432), // XK_Cyrillic_ve
+    (0x06d8, 0x044c), // XK_Cyrillic_softsign
+    (0x06d9, 0x044b), // XK_Cyrillic_yeru
+    (0x06da, 0x0437), // XK_Cyrillic_ze
+    (0x06db, 0x0448), // XK_Cyrillic_sha
+    (0x06dc, 0x044d), // XK_Cyrillic_e
+    (0x06dd, 0x0449), // XK_Cyrillic_shcha
+    (0x06de, 0x0447), // XK_Cyrillic_che
+    (0x06df, 0x044a), // XK_Cyrillic_hardsign
+    (0x06e0, 0x042e), // XK_Cyrillic_YU
+    (0x06e1, 0x0410), // XK_Cyrillic_A
+    (0x06e2, 0x0411), // XK_Cyrillic_BE
+    (0x06e3, 0x0426), // XK_Cyrillic_TSE
+    (0x06e4, 0x0414), // XK_Cyrillic_DE
+    (0x06e5, 0x0415), // XK_Cyrillic_IE
+    (0x06e6, 0x0424), // XK_Cyrillic_EF
+    (0x06e7, 0x0413), // XK_Cyrillic_GHE
+    (0x06e8, 0x0425), // XK_Cyrillic_HA
+    (0x06e9, 0x0418), // XK_Cyrillic_I
+    (0x06ea, 0x0419), // XK_Cyrillic_SHORTI
+    (0x06eb, 0x041a), // XK_Cyrillic_KA
+    (0x06ec, 0x041b), // XK_Cyrillic_EL
+    (0x06ed, 0x041c), // XK_Cyrillic_EM
+    (0x06ee, 0x041d), // XK_Cyrillic_EN
+    (0x06ef, 0x041e), // XK_Cyrillic_O
+    (0x06f0, 0x041f), // XK_Cyrillic_PE
+    (0x06f1, 0x042f), // XK_Cyrillic_YA
+    (0x06f2, 0x0420), // XK_Cyrillic_ER
+    (0x06f3, 0x0421), // XK_Cyrillic_ES
+    (0x06f4, 0x0422), // XK_Cyrillic_TE
+    (0x06f5, 0x0423), // XK_Cyrillic_U
+    (0x06f6, 0x0416), // XK_Cyrillic_ZHE
+    (0x06f7, 0x0412), // XK_Cyrillic_VE
+    (0x06f8, 0x042c), // XK_Cyrillic_SOFTSIGN
+    (0x06f9, 0x042b), // XK_Cyrillic_YERU
+    (0x06fa, 0x0417), // XK_Cyrillic_ZE
+    (0x06fb, 0x0428), // XK_Cyrillic_SHA
+    (0x06fc, 0x042d), // XK_Cyrillic_E
+    (0x06fd, 0x0429), // XK_Cyrillic_SHCHA
+    (0x06fe, 0x0427), // XK_Cyrillic_CHE
+    (0x06ff, 0x042a), // XK_Cyrillic_HARDSIGN
+    // Greek
+    (0x07a1, 0x0386), // XK_Greek_ALPHAaccent
+    (0x07a2, 0x0388), // XK_Greek_EPSILONaccent
+    (0x07a3, 0x0389), // XK_Greek_ETAaccent
+    (0x07a4, 0x038a), // XK_Greek_IOTAaccent
+    (0x07a5, 0x03aa), // XK_Greek_IOTAdieresis
+    (0x07a7, 0x038c), // XK_Greek_OMICRONaccent
+    (0x07a8, 0x038e), // XK_Greek_UPSILONaccent
+    (0x07a9, 0x03ab), // XK_Greek_UPSILONdieresis
+    (0x07ab, 0x038f), // XK_Greek_OMEGAaccent
+    (0x07ae, 0x0385), // XK_Greek_accentdieresis
+    (0x07af, 0x2015), // XK_Greek_horizbar
+    (0x07b1, 0x03ac), // XK_Greek_alphaaccent
+    (0x07b2, 0x03ad), // XK_Greek_epsilonaccent
+    (0x07b3, 0x03ae), // XK_Greek_etaaccent
+    (0x07b4, 0x03af), // XK_Greek_iotaaccent
+    (0x07b5, 0x03ca), // XK_Greek_iotadieresis
+    (0x07b6, 0x0390), // XK_Greek_iotaaccentdieresis
+    (0x07b7, 0x03cc), // XK_Greek_omicronaccent
+    (0x07b8, 0x03cd), // XK_Greek_upsilonaccent
+    (0x07b9, 0x03cb), // XK_Greek_upsilondieresis
+    (0x07ba, 0x03b0), // XK_Greek_upsilonaccentdieresis
+    (0x07bb, 0x03ce), // XK_Greek_omegaaccent
+    (0x07c1, 0x0391), // XK_Greek_ALPHA
+    (0x07c2, 0x0392), // XK_Greek_BETA
+    (0x07c3, 0x0393), // XK_Greek_GAMMA
+    (0x07c4, 0x0394), // XK_Greek_DELTA
+    (0x07c5, 0x0395), // XK_Greek_EPSILON
+    (0x07c6, 0x0396), // XK_Greek_ZETA
+    (0x07c7, 0x0397), // XK_Greek_ETA
+    (0x07c8, 0x0398), // XK_Greek_THETA
+    (0x07c9, 0x0399), // XK_Greek_IOTA
+    (0x07ca, 0x039a), // XK_Greek_KAPPA
+    (0x07cb, 0x039b), // XK_Greek_LAMDA
+    (0x07cc, 0x039c), // XK_Greek_MU
+    (0x07cd, 0x039d), // XK_Greek_NU
+    (0x07ce, 0x039e), // XK_Greek_XI
+    (0x07cf, 0x039f), // XK_Greek_OMICRON
+    (0x07d0, 0x03a0), // XK_Greek_PI
+    (0x07d1, 0x03a1), // XK_Greek_RHO
+    (0x07d2, 0x03a3), // XK_Greek_SIGMA
+    (0x07d4, 0x03a4), // XK_Greek_TAU
+    (0x07d5, 0x03a5), // XK_Greek_UPSILON
+    (0x07d6, 0x03a6), // XK_Greek_PHI
+    (0x07d7, 0x03a7), // XK_Greek_CHI
+    (0x07d8, 0x03a8), // XK_Greek_PSI
+    (0x07d9, 0x03a9), // XK_Greek_OMEGA
+    (0x07e1, 0x03b1), // XK_Greek_alpha
+    (0x07e2, 0x03b2), // XK_Greek_beta
+    (0x07e3, 0x03b3), // XK_Greek_gamma
+    (0x07e4, 0x03b4), // XK_Greek_delta
+    (0x07e5, 0x03b5), // XK_Greek_epsilon
+    (0x07e6, 0x03b6), // XK_Greek_zeta
+    (0x07e7, 0x03b7), // XK_Greek_eta
+    (0x07e8, 0x03b8), // XK_Greek_theta
+    (0x07e9, 0x03b9), // XK_Greek_iota
+    (0x07ea, 0x03ba), // XK_Greek_kappa
+    (0x07eb, 0x03bb), // XK_Greek_lamda
+    (0x07ec, 0x03bc), // XK_Greek_mu
+    (0x07ed, 0x03bd), // XK_Greek_nu
+    (0x07ee, 0x03be), // XK_Greek_xi
+    (0x07ef, 0x03bf), // XK_Greek_omicron
+    (0x07f0, 0x03c0), // XK_Greek_pi
+    (0x07f1, 0x03c1), // XK_Greek_rho
+    (0x07f2, 0x03c3), // XK_Greek_sigma
+    (0x07f3, 0x03c2), // XK_Greek_finalsmallsigma
+    (0x07f4, 0x03c4), // XK_Greek_tau
+    (0x07f5, 0x03c5), // XK_Greek_upsilon
+    (0x07f6, 0x03c6), // XK_Greek_phi
+    (0x07f7, 0x03c7), // XK_Greek_chi
+    (0x07f8, 0x03c8), // XK_Greek_psi
+    (0x07f9, 0x03c9), // XK_Greek_omega
+    // Latin-9
+    (0x13bc, 0x0152), // XK_OE
+    (0x13bd, 0x0153), // XK_oe
+    (0x13be, 0x0178), // XK_Ydiaeresis
 ];
+
+/// Convert a legacy, non-Latin-1 keysym into the character it stands for.
+pub fn legacy_keysym_to_char(ks: u32) -> Option<char> {
+    let code = match ks {
+        // Arabic letters
+        0x05c1..=0x05da => ks - 0x05c1 + 0x0621,
+        0x05e0..=0x05f2 => ks - 0x05e0 + 0x0640,
+        // Hebrew
+        0x0cdf => 0x2017,
+        0x0ce0..=0x0cfa => ks - 0x0ce0 + 0x05d0,
+        // Thai
+        0x0da1..=0x0dda | 0x0ddf..=0x0df9 => ks - 0x0da0 + 0x0e00,
+        // currency symbols, including the euro sign
+        0x20a0..=0x20ac => ks,
+        _ => {
+            let table = LEGACY_KEYSYM_TABLE;
+            let i = table.binary_search_by_key(&ks, |&(k, _)| k).ok()?;
+            table[i].1 as u32
+        }
+    };
+    core::char::from_u32(code)
+}
+
+#[test]
+fn test_x11_keysym_table() {
+    // the table needs to be strictly sorted for the binary search to work
+    assert!(X11_KEYSYM_TABLE.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(LEGACY_KEYSYM_TABLE.windows(2).all(|w| w[0].0 < w[1].0));
+
+    assert_eq!(KeyType::from_keysym(0x0041), A);
+    assert_eq!(KeyType::from_keysym(0xffbe), F1);
+    assert_eq!(KeyType::from_keysym(0x1008_ff13), VolumeUp);
+    assert_eq!(KeyType::from_keysym(0x00e9), Char('é'));
+    assert_eq!(KeyType::from_keysym(0x0100_0041), A);
+    assert_eq!(KeyType::from_keysym(0x0100_263a), Char('☺'));
+    assert_eq!(KeyType::from_keysym(0xfe50), Unknown);
+
+    // legacy keysyms for other scripts
+    assert_eq!(KeyType::from_keysym(0x01a3), Char('Ł'));
+    assert_eq!(KeyType::from_keysym(0x06c1), Char('а'));
+    assert_eq!(KeyType::from_keysym(0x06e1), Char('А'));
+    assert_eq!(KeyType::from_keysym(0x07e1), Char('α'));
+    assert_eq!(KeyType::from_keysym(0x07f3), Char('ς'));
+    assert_eq!(KeyType::from_keysym(0x0ce0), Char('א'));
+    assert_eq!(KeyType::from_keysym(0x20a3), Char('₣'));
+    assert_eq!(KeyType::from_keysym(0x20ac), EuroSign);
+}
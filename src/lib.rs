@@ -64,24 +64,35 @@ extern crate std as alloc;
 //#![cfg_attr(target_os = "linux", feature("flutterbug"))]
 //#![cfg_attr(windows, feature("porcupine"))]
 pub mod arc;
+pub mod clock;
 pub mod color;
+pub mod cursor;
 pub mod error;
 pub mod event;
+pub mod gesture;
 pub mod graphics;
 pub mod instance;
 pub mod keyboard;
 pub mod mouse;
 pub mod pixel_buffer;
 pub mod ro_mmg;
+pub mod signal;
+pub mod stats;
 pub(crate) mod take_vec;
 pub mod texture;
 pub mod window;
 pub(crate) mod wndproc;
+pub(crate) mod xerror;
 
 pub use arc::*;
+pub use clock::{Clock, ManualClock};
+#[cfg(feature = "std")]
+pub use clock::SystemClock;
 pub use color::*;
+pub use cursor::Cursor;
 pub use error::*;
 pub use event::*;
+pub use gesture::GestureConfig;
 pub use graphics::*;
 pub use instance::*;
 pub use keyboard::*;
@@ -89,6 +100,9 @@ pub use mouse::*;
 pub use pixel_buffer::PixelBuffer;
 #[cfg(feature = "std")]
 pub use ro_mmg::*;
+#[cfg(target_os = "linux")]
+pub use signal::Signal;
+pub use stats::{InstanceStats, Timing};
 pub use texture::*;
 pub use window::*;
 
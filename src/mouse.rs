@@ -43,12 +43,60 @@
  * ----------------------------------------------------------------------------------
  */
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// The possible buttons that can be depressed on the mouse.
+///
+/// Note that scroll wheels are reported through `Scroll` events rather than as button
+/// presses.
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MouseButton {
     Button1,
     Button2,
     Button3,
+    /// Deprecated: buttons 4 and 5 are the scroll wheel, which is reported through
+    /// `Scroll` events, so this is never emitted.
     Button4,
+    /// Deprecated: buttons 4 and 5 are the scroll wheel, which is reported through
+    /// `Scroll` events, so this is never emitted.
     Button5,
+    /// The "back" button found on the side of many mice.
+    Back,
+    /// The "forward" button found on the side of many mice.
+    Forward,
+    /// Any other button, identified by its number.
+    Other(u8),
+}
+
+bitflags::bitflags! {
+    /// The mouse buttons that are currently held down.
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct ButtonState: u8 {
+        const BUTTON1 = 1 << 0;
+        const BUTTON2 = 1 << 1;
+        const BUTTON3 = 1 << 2;
+        /// Never set on X11, where buttons 4 and 5 are the scroll wheel.
+        const BUTTON4 = 1 << 3;
+        /// Never set on X11, where buttons 4 and 5 are the scroll wheel.
+        const BUTTON5 = 1 << 4;
+    }
+}
+
+impl ButtonState {
+    /// Tell if a certain mouse button is held down. Buttons past the fifth are not
+    /// tracked, so this always returns false for them.
+    #[inline]
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.contains(match button {
+            MouseButton::Button1 => Self::BUTTON1,
+            MouseButton::Button2 => Self::BUTTON2,
+            MouseButton::Button3 => Self::BUTTON3,
+            MouseButton::Button4 => Self::BUTTON4,
+            MouseButton::Button5 => Self::BUTTON5,
+            _ => return false,
+        })
+    }
 }
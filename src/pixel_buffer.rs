@@ -47,9 +47,12 @@ use crate::Color;
 use alloc::{boxed::Box, vec::Vec};
 use core::fmt;
 use euclid::default::{Point2D, Size2D};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Possible formats for the pixel buffer.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Format {
     Grayscale,
     Rgb,
@@ -74,12 +77,7 @@ impl Formatter for GrayscaleFormatter {
 
     #[inline]
     fn get_color(&self, bytes: &[u8]) -> crate::Result<Color> {
-        Ok(Color::from_rgba(
-            bytes[0],
-            bytes[0],
-            bytes[0],
-            core::u8::MAX,
-        ))
+        Ok(Color::from_rgba(bytes[0], bytes[0], bytes[0], u8::MAX))
     }
 }
 
@@ -94,12 +92,7 @@ impl Formatter for RgbFormatter {
     }
     #[inline]
     fn get_color(&self, bytes: &[u8]) -> crate::Result<Color> {
-        Ok(Color::from_rgba(
-            bytes[0],
-            bytes[1],
-            bytes[2],
-            core::u8::MAX,
-        ))
+        Ok(Color::from_rgba(bytes[0], bytes[1], bytes[2], u8::MAX))
     }
 }
 
@@ -189,18 +182,19 @@ impl<'a> Iterator for PixelBufferIter<'a> {
         let num_next_bytes = self.fetcher.formatter().bytes_per_color();
         let length = self.data.len();
 
-        if self.current_pix + num_next_bytes + 1 > length {
+        if self.current_pix + num_next_bytes > length {
             None
         } else {
             let end_index = self.current_pix + num_next_bytes;
             let sl = &self.data[self.current_pix..end_index];
+            self.current_pix = end_index;
             self.fetcher.formatter().get_color(sl).ok()
         }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         let len = (self.data.len() - self.current_pix) / self.fetcher.formatter().bytes_per_color();
-        (0, Some(len))
+        (len, Some(len))
     }
 }
 
@@ -223,6 +217,39 @@ impl PixelBuffer {
         self.fetcher.format()
     }
 
+    /// Get the size of this image, in pixels.
+    #[inline]
+    pub fn size(&self) -> Size2D<usize> {
+        self.size
+    }
+
+    /// Whether there are enough bytes in this buffer to cover every pixel of its size.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.size
+            .width
+            .checked_mul(self.size.height)
+            .and_then(|area| area.checked_mul(self.fetcher.formatter().bytes_per_color()))
+            .is_some_and(|len| self.data.len() >= len)
+    }
+
+    /// Convert the pixels of this image into 32-bit ARGB values, row by row.
+    pub fn to_argb(&self) -> Vec<u32> {
+        let format = self.format();
+        self.data
+            .chunks_exact(self.fetcher.formatter().bytes_per_color())
+            .take(self.size.area())
+            .map(|px| {
+                let (r, g, b, a) = match format {
+                    Format::Grayscale => (px[0], px[0], px[0], u8::MAX),
+                    Format::Rgb => (px[0], px[1], px[2], u8::MAX),
+                    Format::Rgba => (px[0], px[1], px[2], px[3]),
+                };
+                u32::from_be_bytes([a, r, g, b])
+            })
+            .collect()
+    }
+
     /// Create an iterator that iterates over the pixels.
     #[inline]
     pub fn iter(&self) -> PixelBufferIter<'_> {
@@ -245,3 +272,33 @@ impl PixelBuffer {
         self.iter().nth(self.xy_to_index(pt.x, pt.y))
     }
 }
+
+#[test]
+fn test_pixel_buffer() {
+    use alloc::vec;
+    use euclid::{point2, size2};
+
+    let pb = PixelBuffer::new(
+        vec![255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, 1, 2, 3, 4],
+        size2(2, 2),
+        Format::Rgba,
+    );
+    assert_eq!(pb.iter().count(), 4);
+    assert_eq!(pb.at(point2(1, 0)), Some(Color::from_rgba(0, 255, 0, 128)));
+    assert_eq!(pb.at(point2(1, 1)), Some(Color::from_rgba(1, 2, 3, 4)));
+    assert_eq!(
+        pb.to_argb(),
+        vec![0xffff0000, 0x8000ff00, 0x000000ff, 0x04010203]
+    );
+
+    assert!(pb.is_complete());
+
+    let pb = PixelBuffer::new(vec![7, 9], size2(2, 1), Format::Grayscale);
+    assert_eq!(pb.to_argb(), vec![0xff070707, 0xff090909]);
+    assert!(pb.is_complete());
+
+    let pb = PixelBuffer::new(vec![1, 2, 3, 4, 5], size2(1, 2), Format::Rgb);
+    assert!(!pb.is_complete());
+    let pb = PixelBuffer::new(vec![], size2(usize::MAX, 2), Format::Rgba);
+    assert!(!pb.is_complete());
+}
@@ -0,0 +1,190 @@
+/* -----------------------------------------------------------------------------------
+ * src/signal.rs - Translation of POSIX signals into events.
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+#![cfg(target_os = "linux")]
+
+use core::{
+    mem, ptr,
+    sync::atomic::{AtomicI32, Ordering},
+};
+use libc::{c_int, c_void};
+use smallvec::SmallVec;
+
+/// POSIX signals that can be translated into `Quit` events.
+///
+/// See `Instance::handle_signals` for more information.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// SIGINT, usually sent by pressing Ctrl+C in a terminal.
+    Interrupt,
+    /// SIGTERM, the polite request to terminate.
+    Terminate,
+    /// SIGHUP, sent when the controlling terminal is closed.
+    Hangup,
+    /// SIGQUIT, usually sent by pressing Ctrl+\ in a terminal.
+    Quit,
+}
+
+impl Signal {
+    /// Get the raw signal number.
+    #[inline]
+    pub fn as_raw(self) -> c_int {
+        match self {
+            Signal::Interrupt => libc::SIGINT,
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Hangup => libc::SIGHUP,
+            Signal::Quit => libc::SIGQUIT,
+        }
+    }
+
+    /// Convert a raw signal number to a signal.
+    #[inline]
+    pub fn from_raw(signum: c_int) -> Option<Signal> {
+        match signum {
+            libc::SIGINT => Some(Signal::Interrupt),
+            libc::SIGTERM => Some(Signal::Terminate),
+            libc::SIGHUP => Some(Signal::Hangup),
+            libc::SIGQUIT => Some(Signal::Quit),
+            _ => None,
+        }
+    }
+}
+
+// the write end of the pipe, as seen by the signal handler
+static SIGNAL_PIPE_WRITE: AtomicI32 = AtomicI32::new(-1);
+
+// Note: this runs inside of the signal handler, so it can only use async-signal-safe
+//       functions. write() is one of them.
+extern "C" fn signal_handler(signum: c_int) {
+    // errno belongs to whatever the signal interrupted, and write() can change it
+    let errno = unsafe { *libc::__errno_location() };
+
+    let fd = SIGNAL_PIPE_WRITE.load(Ordering::SeqCst);
+    if fd >= 0 {
+        let byte = signum as u8;
+        unsafe { libc::write(fd, &byte as *const u8 as *const c_void, 1) };
+    }
+
+    unsafe { *libc::__errno_location() = errno };
+}
+
+/// A self-pipe that signal handlers write into, in order to wake up the event loop.
+pub(crate) struct SignalPipe {
+    read: c_int,
+    write: c_int,
+}
+
+impl SignalPipe {
+    /// Create a new signal pipe. Both ends are non-blocking.
+    pub(crate) fn new() -> crate::Result<Self> {
+        let mut fds: [c_int; 2] = [-1, -1];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) } != 0 {
+            return Err(crate::Error::StaticMsg("Unable to create signal pipe"));
+        }
+
+        Ok(Self {
+            read: fds[0],
+            write: fds[1],
+        })
+    }
+
+    /// Install the signal handler for the specified signals.
+    pub(crate) fn install(&self, signals: &[Signal]) -> crate::Result<()> {
+        SIGNAL_PIPE_WRITE.store(self.write, Ordering::SeqCst);
+
+        signals.iter().try_for_each(|s| {
+            let mut action: libc::sigaction = unsafe { mem::zeroed() };
+            action.sa_sigaction = signal_handler as extern "C" fn(c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            unsafe { libc::sigemptyset(&mut action.sa_mask) };
+
+            if unsafe { libc::sigaction(s.as_raw(), &action, ptr::null_mut()) } != 0 {
+                Err(crate::Error::StaticMsg("Unable to install signal handler"))
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// The end of the pipe that should be polled.
+    #[inline]
+    pub(crate) fn read_fd(&self) -> c_int {
+        self.read
+    }
+
+    /// Read every signal that has been written into the pipe.
+    pub(crate) fn drain(&self) -> SmallVec<[Signal; 1]> {
+        let mut signals = SmallVec::new();
+        let mut buffer = [0u8; 16];
+
+        loop {
+            let len =
+                unsafe { libc::read(self.read, buffer.as_mut_ptr() as *mut c_void, buffer.len()) };
+            if len <= 0 {
+                break;
+            }
+
+            buffer[..len as usize]
+                .iter()
+                .filter_map(|b| Signal::from_raw(*b as c_int))
+                .for_each(|s| signals.push(s));
+        }
+
+        signals
+    }
+}
+
+impl Drop for SignalPipe {
+    fn drop(&mut self) {
+        // make sure the signal handler doesn't write into a closed file
+        let _ =
+            SIGNAL_PIPE_WRITE.compare_exchange(self.write, -1, Ordering::SeqCst, Ordering::SeqCst);
+
+        unsafe {
+            libc::close(self.read);
+            libc::close(self.write);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+/* -----------------------------------------------------------------------------------
+ * src/stats.rs - Event loop statistics and instrumentation.
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+use crate::{Clock, EventType};
+use core::time::Duration;
+use hashbrown::HashMap;
+
+/// Timing information collected for a single kind of operation.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Timing {
+    count: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Timing {
+    /// The number of times the operation has been measured.
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The total amount of time spent in the operation.
+    #[inline]
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// The longest single run of the operation.
+    #[inline]
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// The average amount of time spent in the operation.
+    #[inline]
+    pub fn average(&self) -> Duration {
+        match self.count {
+            0 => Duration::default(),
+            count => self.total / count as u32,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        if elapsed > self.max {
+            self.max = elapsed;
+        }
+    }
+}
+
+/// A snapshot of the statistics collected by an Instance's event loop.
+///
+/// This is retrieved using `Instance::stats()`. Timing information is measured with the
+/// Instance's clock.
+#[derive(Debug, Default, Clone)]
+pub struct InstanceStats {
+    queue_depth: usize,
+    peak_queue_depth: usize,
+    translated: HashMap<EventType, u64>,
+    dropped: u64,
+    handler_times: HashMap<usize, Timing>,
+    paint_times: Timing,
+}
+
+impl InstanceStats {
+    /// The number of events waiting in the event queue at the time of the snapshot.
+    #[inline]
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// The largest number of events that have been waiting in the event queue at once.
+    #[inline]
+    pub fn peak_queue_depth(&self) -> usize {
+        self.peak_queue_depth
+    }
+
+    /// The number of events of a certain type that have been translated from the backend.
+    #[inline]
+    pub fn translated(&self, ty: &EventType) -> u64 {
+        self.translated.get(ty).copied().unwrap_or(0)
+    }
+
+    /// The number of events translated from the backend, sorted by type.
+    #[inline]
+    pub fn translated_by_type(&self) -> &HashMap<EventType, u64> {
+        &self.translated
+    }
+
+    /// The total number of events translated from the backend.
+    #[inline]
+    pub fn total_translated(&self) -> u64 {
+        self.translated.values().sum()
+    }
+
+    /// The number of events that have been dropped because their target window did not
+    /// receive that type of event.
+    #[inline]
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// The time spent in the event handler of the window with the specified ID.
+    #[inline]
+    pub fn handler_time(&self, window_id: usize) -> Timing {
+        self.handler_times
+            .get(&window_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The time spent in event handlers, sorted by the IDs of their windows.
+    #[inline]
+    pub fn handler_times(&self) -> &HashMap<usize, Timing> {
+        &self.handler_times
+    }
+
+    /// The time spent dispatching paint events.
+    #[inline]
+    pub fn paint_times(&self) -> Timing {
+        self.paint_times
+    }
+
+    #[inline]
+    pub(crate) fn set_queue_depth(&mut self, depth: usize) {
+        self.queue_depth = depth;
+        if depth > self.peak_queue_depth {
+            self.peak_queue_depth = depth;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record_translated(&mut self, ty: EventType) {
+        *self.translated.entry(ty).or_insert(0) += 1;
+    }
+
+    #[inline]
+    pub(crate) fn record_dropped(&mut self, count: u64) {
+        self.dropped += count;
+    }
+
+    #[inline]
+    pub(crate) fn record_handler_time(&mut self, window_id: usize, elapsed: Duration) {
+        self.handler_times
+            .entry(window_id)
+            .or_default()
+            .record(elapsed);
+    }
+
+    #[inline]
+    pub(crate) fn record_paint_time(&mut self, elapsed: Duration) {
+        self.paint_times.record(elapsed);
+    }
+}
+
+/// Run a function and measure how long it takes, according to a clock.
+#[inline]
+pub(crate) fn timed<T, F: FnOnce() -> T>(clock: &dyn Clock, f: F) -> (T, Duration) {
+    let start = clock.now();
+    let res = f();
+
+    // a manual clock may have been set backwards in the meantime
+    let elapsed = clock.now().checked_sub(start).unwrap_or_default();
+    (res, elapsed)
+}
+
+#[test]
+fn test_instance_stats() {
+    use crate::ManualClock;
+
+    let clock = ManualClock::new();
+    let mut stats = InstanceStats::default();
+
+    let (_, elapsed) = timed(&clock, || clock.advance(Duration::from_millis(5)));
+    stats.record_handler_time(1, elapsed);
+    let (_, elapsed) = timed(&clock, || clock.advance(Duration::from_millis(15)));
+    stats.record_handler_time(1, elapsed);
+    let (_, elapsed) = timed(&clock, || clock.set(Duration::from_millis(0)));
+    stats.record_paint_time(elapsed);
+
+    let handler = stats.handler_time(1);
+    assert_eq!(handler.count(), 2);
+    assert_eq!(handler.total(), Duration::from_millis(20));
+    assert_eq!(handler.max(), Duration::from_millis(15));
+    assert_eq!(handler.average(), Duration::from_millis(10));
+    assert_eq!(stats.handler_time(2), Timing::default());
+    assert_eq!(stats.paint_times().total(), Duration::from_millis(0));
+
+    stats.record_translated(EventType::KeyDown);
+    stats.record_translated(EventType::KeyDown);
+    stats.record_translated(EventType::Paint);
+    stats.record_dropped(1);
+    assert_eq!(stats.translated(&EventType::KeyDown), 2);
+    assert_eq!(stats.total_translated(), 3);
+    assert_eq!(stats.dropped(), 1);
+
+    stats.set_queue_depth(4);
+    stats.set_queue_depth(1);
+    assert_eq!(stats.queue_depth(), 1);
+    assert_eq!(stats.peak_queue_depth(), 4);
+}
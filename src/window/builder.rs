@@ -0,0 +1,207 @@
+/* -----------------------------------------------------------------------------------
+ * src/window/builder.rs - Builder for configuring windows before creation
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+use super::Window;
+use crate::{Instance, Texture};
+use alloc::string::String;
+use euclid::default::Rect;
+
+bitflags::bitflags! {
+    /// Flags that describe how a window is presented by the window manager.
+    ///
+    /// These only apply to top-level windows.
+    pub struct WindowStyle: u32 {
+        /// The window can be resized by the user.
+        const RESIZABLE = 1 << 0;
+        /// The window has a title bar and border.
+        const DECORATED = 1 << 1;
+        /// The window can be closed by the user.
+        const CLOSABLE = 1 << 2;
+        /// The window is kept above other windows.
+        const ALWAYS_ON_TOP = 1 << 3;
+        /// The window does not appear in the taskbar.
+        const SKIP_TASKBAR = 1 << 4;
+    }
+}
+
+impl Default for WindowStyle {
+    #[inline]
+    fn default() -> Self {
+        Self::RESIZABLE | Self::DECORATED | Self::CLOSABLE
+    }
+}
+
+/// The purpose of a top-level window, which the window manager uses to decide how to
+/// treat it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WindowType {
+    /// An ordinary application window.
+    Normal,
+    /// A dialog box.
+    Dialog,
+    /// A small persistent window, such as a palette or toolbox.
+    Utility,
+    /// A splash screen that is shown while the application starts.
+    Splash,
+    /// A tooltip.
+    Tooltip,
+}
+
+impl Default for WindowType {
+    #[inline]
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// Configures a window before it is created.
+///
+/// # Example
+///
+/// ```no_run
+/// use beetle::{Instance, WindowBuilder, WindowStyle, WindowType};
+/// use euclid::rect;
+///
+/// # fn main() -> beetle::Result<()> {
+/// let instance = Instance::new()?;
+/// let dialog = WindowBuilder::new()
+///     .with_text("Settings".to_string())
+///     .with_bounds(rect(0, 0, 300, 200))
+///     .with_style(WindowStyle::DECORATED | WindowStyle::CLOSABLE)
+///     .with_window_type(WindowType::Dialog)
+///     .with_visible(true)
+///     .build(&instance)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct WindowBuilder {
+    pub(crate) parent: Option<Window>,
+    pub(crate) text: String,
+    pub(crate) bounds: Rect<u32>,
+    pub(crate) background: Option<Texture>,
+    pub(crate) style: WindowStyle,
+    pub(crate) window_type: WindowType,
+    pub(crate) visible: bool,
+}
+
+impl Default for WindowBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            parent: None,
+            text: String::new(),
+            bounds: euclid::rect(0, 0, 640, 480),
+            background: None,
+            style: WindowStyle::default(),
+            window_type: WindowType::default(),
+            visible: false,
+        }
+    }
+}
+
+impl WindowBuilder {
+    /// Create a new window builder. By default, the window is a hidden, resizable,
+    /// decorated top-level window.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the parent of the window. Windows without a parent are top-level windows.
+    #[inline]
+    pub fn with_parent(mut self, parent: &Window) -> Self {
+        self.parent = Some(parent.clone());
+        self
+    }
+
+    /// Set the text associated with the window.
+    #[inline]
+    pub fn with_text(mut self, text: String) -> Self {
+        self.text = text;
+        self
+    }
+
+    /// Set the initial bounds of the window.
+    #[inline]
+    pub fn with_bounds(mut self, bounds: Rect<u32>) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    /// Set the background of the window.
+    #[inline]
+    pub fn with_background(mut self, background: Texture) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Set the style flags of the window.
+    #[inline]
+    pub fn with_style(mut self, style: WindowStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the type of the window.
+    #[inline]
+    pub fn with_window_type(mut self, window_type: WindowType) -> Self {
+        self.window_type = window_type;
+        self
+    }
+
+    /// Set whether the window is shown as soon as it is created.
+    #[inline]
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Create the window.
+    #[inline]
+    pub fn build(self, instance: &Instance) -> crate::Result<Window> {
+        instance.build_window(self)
+    }
+}
@@ -0,0 +1,183 @@
+/* -----------------------------------------------------------------------------------
+ * src/window/constraints.rs - Limits on the size of a window
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+use euclid::default::Size2D;
+
+/// Limits on the size that a window can take.
+///
+/// These are enforced by Beetle when the bounds are set programmatically, and passed on
+/// to the window manager so it enforces them when the user resizes the window.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SizeConstraints {
+    min: Option<Size2D<u32>>,
+    max: Option<Size2D<u32>>,
+    aspect_ratio: Option<(u32, u32)>,
+    increments: Option<Size2D<u32>>,
+}
+
+impl SizeConstraints {
+    /// Create a new set of size constraints that doesn't constrain anything.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a set of size constraints that only allows one size.
+    #[inline]
+    pub fn fixed(size: Size2D<u32>) -> Self {
+        Self {
+            min: Some(size),
+            max: Some(size),
+            ..Self::default()
+        }
+    }
+
+    /// The minimum size of the window.
+    #[inline]
+    pub fn min(&self) -> Option<Size2D<u32>> {
+        self.min
+    }
+
+    /// The maximum size of the window.
+    #[inline]
+    pub fn max(&self) -> Option<Size2D<u32>> {
+        self.max
+    }
+
+    /// Set the minimum and maximum size of the window.
+    #[inline]
+    pub fn set_limits(&mut self, min: Option<Size2D<u32>>, max: Option<Size2D<u32>>) {
+        self.min = min;
+        self.max = max;
+    }
+
+    /// The ratio of width to height that the window is kept at.
+    #[inline]
+    pub fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.aspect_ratio
+    }
+
+    /// Set the ratio of width to height that the window is kept at.
+    #[inline]
+    pub fn set_aspect_ratio(&mut self, ratio: Option<(u32, u32)>) {
+        self.aspect_ratio = ratio.filter(|&(w, h)| w != 0 && h != 0);
+    }
+
+    /// The steps that the size of the window changes in.
+    #[inline]
+    pub fn increments(&self) -> Option<Size2D<u32>> {
+        self.increments
+    }
+
+    /// Set the steps that the size of the window changes in. Steps are counted from the
+    /// minimum size, if there is one.
+    #[inline]
+    pub fn set_increments(&mut self, increments: Option<Size2D<u32>>) {
+        self.increments = increments.filter(|i| i.width != 0 && i.height != 0);
+    }
+
+    /// Get the size closest to the given size that fits these constraints. If the
+    /// constraints conflict, the minimum and maximum size win.
+    pub fn clamp(&self, size: Size2D<u32>) -> Size2D<u32> {
+        let mut size = size;
+
+        // snap down to the nearest increment above the base size
+        if let Some(inc) = self.increments {
+            let base = self.min.unwrap_or_else(Size2D::zero);
+            if size.width > base.width {
+                size.width -= (size.width - base.width) % inc.width;
+            }
+            if size.height > base.height {
+                size.height -= (size.height - base.height) % inc.height;
+            }
+        }
+
+        // shrink whichever dimension is too large for the ratio
+        if let Some((rw, rh)) = self.aspect_ratio {
+            let (w, h) = (size.width as u64, size.height as u64);
+            let (rw, rh) = (rw as u64, rh as u64);
+            if w * rh > h * rw {
+                size.width = (h * rw / rh) as u32;
+            } else {
+                size.height = (w * rh / rw) as u32;
+            }
+        }
+
+        if let Some(min) = self.min {
+            size = size.max(min);
+        }
+        if let Some(max) = self.max {
+            size = size.min(max);
+        }
+
+        size
+    }
+}
+
+#[test]
+fn test_size_constraints() {
+    use euclid::size2;
+
+    let mut c = SizeConstraints::new();
+    assert_eq!(c.clamp(size2(5, 5)), size2(5, 5));
+
+    c.set_limits(Some(size2(100, 50)), Some(size2(800, 600)));
+    assert_eq!(c.clamp(size2(5, 5)), size2(100, 50));
+    assert_eq!(c.clamp(size2(1000, 300)), size2(800, 300));
+
+    c.set_increments(Some(size2(8, 16)));
+    assert_eq!(c.clamp(size2(205, 99)), size2(204, 98));
+
+    let mut c = SizeConstraints::new();
+    c.set_aspect_ratio(Some((16, 9)));
+    assert_eq!(c.clamp(size2(1600, 1600)), size2(1600, 900));
+    assert_eq!(c.clamp(size2(160, 10)), size2(17, 10));
+
+    c.set_aspect_ratio(Some((0, 9)));
+    assert_eq!(c.aspect_ratio(), None);
+
+    let c = SizeConstraints::fixed(size2(3, 4));
+    assert_eq!(c.clamp(size2(9, 9)), size2(3, 4));
+}
@@ -43,18 +43,555 @@
  * ----------------------------------------------------------------------------------
  */
 
-use super::{super::Window, unique_id, EventHandler, GenericWindowInternal};
-use crate::{EventType, Instance, Texture};
-use alloc::{boxed::Box, string::String};
-use core::{convert::TryInto, mem};
-use euclid::default::{Point2D, Rect};
+use super::{unique_id, EventHandler, GenericWindowInternal, WindowBuilder};
+use crate::{
+    Cursor, EventType, Instance, PixelBuffer, SizeConstraints, Texture, WindowState, WindowStyle,
+    WindowType,
+};
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::{
+    convert::TryInto,
+    iter, mem, ptr, slice,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use cty::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong};
+use euclid::default::{Point2D, Rect, Size2D};
 use flutterbug::{
-    prelude::*, Event as FEvent, EventMask, EventType as FEventType, ExposeEvent, InputContext,
-    Window as FWindow,
+    prelude::*, x11::xlib, Atom, Event as FEvent, EventMask, EventType as FEventType, ExposeEvent,
+    InputContext, Window as FWindow,
 };
 use hashbrown::{HashMap, HashSet};
+use libc::c_void;
 use smallvec::{smallvec, SmallVec};
 
+// _MOTIF_WM_HINTS values, from MwmUtil.h
+const MWM_HINTS_FUNCTIONS: c_ulong = 1 << 0;
+const MWM_HINTS_DECORATIONS: c_ulong = 1 << 1;
+const MWM_FUNC_RESIZE: c_ulong = 1 << 1;
+const MWM_FUNC_MOVE: c_ulong = 1 << 2;
+const MWM_FUNC_MINIMIZE: c_ulong = 1 << 3;
+const MWM_FUNC_MAXIMIZE: c_ulong = 1 << 4;
+const MWM_FUNC_CLOSE: c_ulong = 1 << 5;
+const MWM_DECOR_ALL: c_ulong = 1 << 0;
+
+// glyphs in the standard cursor font, from cursorfont.h; the x11 crate doesn't define them
+const XC_BOTTOM_LEFT_CORNER: c_uint = 12;
+const XC_BOTTOM_RIGHT_CORNER: c_uint = 14;
+const XC_CIRCLE: c_uint = 24;
+const XC_CROSSHAIR: c_uint = 34;
+const XC_FLEUR: c_uint = 52;
+const XC_HAND2: c_uint = 60;
+const XC_LEFT_PTR: c_uint = 68;
+const XC_SB_H_DOUBLE_ARROW: c_uint = 108;
+const XC_SB_V_DOUBLE_ARROW: c_uint = 116;
+const XC_WATCH: c_uint = 150;
+const XC_XTERM: c_uint = 152;
+
+// WM_HINTS initial states, from Xutil.h; the x11 crate doesn't define them
+const NORMAL_STATE: c_int = 1;
+const ICONIC_STATE: c_int = 3;
+
+// the _NET_WM_WINDOW_TYPE atom corresponding to a window type
+#[inline]
+fn net_wm_window_type(window_type: WindowType) -> &'static str {
+    match window_type {
+        WindowType::Normal => "_NET_WM_WINDOW_TYPE_NORMAL",
+        WindowType::Dialog => "_NET_WM_WINDOW_TYPE_DIALOG",
+        WindowType::Utility => "_NET_WM_WINDOW_TYPE_UTILITY",
+        WindowType::Splash => "_NET_WM_WINDOW_TYPE_SPLASH",
+        WindowType::Tooltip => "_NET_WM_WINDOW_TYPE_TOOLTIP",
+    }
+}
+
+// replace a property made up of 32-bit values, which Xlib stores as longs
+#[inline]
+pub(crate) fn set_long_property(
+    instance: &Instance,
+    window: xlib::Window,
+    property: &'static str,
+    ty: xlib::Atom,
+    values: &[c_ulong],
+) -> crate::Result<()> {
+    let dpy = instance.raw_display()?;
+    unsafe {
+        xlib::XChangeProperty(
+            dpy,
+            window,
+            instance.atom(property)?,
+            ty,
+            32,
+            xlib::PropModeReplace,
+            values.as_ptr() as *const c_uchar,
+            values.len() as c_int,
+        )
+    };
+    Ok(())
+}
+
+// tell the window manager how to present a top-level window
+fn set_window_style(
+    instance: &Instance,
+    window: xlib::Window,
+    style: WindowStyle,
+    window_type: WindowType,
+) -> crate::Result<()> {
+    // the Motif hints are the de facto way of controlling decorations and functions
+    let mut functions = MWM_FUNC_MOVE | MWM_FUNC_MINIMIZE;
+    if style.contains(WindowStyle::RESIZABLE) {
+        functions |= MWM_FUNC_RESIZE | MWM_FUNC_MAXIMIZE;
+    }
+    if style.contains(WindowStyle::CLOSABLE) {
+        functions |= MWM_FUNC_CLOSE;
+    }
+    let decorations = if style.contains(WindowStyle::DECORATED) {
+        MWM_DECOR_ALL
+    } else {
+        0
+    };
+    let flags = MWM_HINTS_FUNCTIONS | MWM_HINTS_DECORATIONS;
+    let hints = [flags, functions, decorations, 0, 0];
+    let motif_hints = instance.atom("_MOTIF_WM_HINTS")?;
+    set_long_property(instance, window, "_MOTIF_WM_HINTS", motif_hints, &hints)?;
+
+    let window_type = instance.atom(net_wm_window_type(window_type))?;
+    set_long_property(
+        instance,
+        window,
+        "_NET_WM_WINDOW_TYPE",
+        xlib::XA_ATOM,
+        &[window_type],
+    )?;
+
+    // before the window is mapped, the client sets _NET_WM_STATE directly
+    let mut states: SmallVec<[c_ulong; 2]> = SmallVec::new();
+    if style.contains(WindowStyle::ALWAYS_ON_TOP) {
+        states.push(instance.atom("_NET_WM_STATE_ABOVE")?);
+    }
+    if style.contains(WindowStyle::SKIP_TASKBAR) {
+        states.push(instance.atom("_NET_WM_STATE_SKIP_TASKBAR")?);
+    }
+    set_long_property(instance, window, "_NET_WM_STATE", xlib::XA_ATOM, &states)?;
+
+    Ok(())
+}
+
+// tell if the window manager is managing a window. it marks the windows that it manages
+// with the WM_STATE property
+fn is_managed(instance: &Instance, window: xlib::Window) -> crate::Result<bool> {
+    let mut actual_type: xlib::Atom = 0;
+    let mut actual_format: c_int = 0;
+    let mut nitems: c_ulong = 0;
+    let mut bytes_after: c_ulong = 0;
+    let mut prop: *mut c_uchar = ptr::null_mut();
+
+    unsafe {
+        xlib::XGetWindowProperty(
+            instance.raw_display()?,
+            window,
+            instance.atom("WM_STATE")?,
+            0,
+            0,
+            xlib::False,
+            xlib::AnyPropertyType as xlib::Atom,
+            &mut actual_type,
+            &mut actual_format,
+            &mut nitems,
+            &mut bytes_after,
+            &mut prop,
+        );
+        if !prop.is_null() {
+            xlib::XFree(prop as *mut _);
+        }
+    }
+
+    Ok(actual_type != 0)
+}
+
+// modify the WM_HINTS property of a window
+fn update_wm_hints<F>(dpy: *mut xlib::Display, window: xlib::Window, f: F)
+where
+    F: FnOnce(&mut xlib::XWMHints),
+{
+    unsafe {
+        let mut hints = xlib::XGetWMHints(dpy, window);
+        if hints.is_null() {
+            hints = xlib::XAllocWMHints();
+            if hints.is_null() {
+                return;
+            }
+        }
+
+        f(&mut *hints);
+        xlib::XSetWMHints(dpy, window, hints);
+        xlib::XFree(hints as *mut _);
+    }
+}
+
+// set the state that the window manager puts the window in when it's first mapped
+#[inline]
+fn set_initial_state(dpy: *mut xlib::Display, window: xlib::Window, state: c_int) {
+    update_wm_hints(dpy, window, |hints| {
+        hints.flags |= xlib::StateHint;
+        hints.initial_state = state;
+    });
+}
+
+// the size of icon that window managers which only read WM_HINTS tend to use
+const LEGACY_ICON_SIZE: usize = 48;
+
+// create a pixmap of an icon, and a mask from its alpha channel, for WM_HINTS
+fn icon_pixmaps(
+    dpy: *mut xlib::Display,
+    window: xlib::Window,
+    icon: &PixelBuffer,
+) -> Option<(xlib::Pixmap, xlib::Pixmap)> {
+    let size = icon.size();
+    let (width, height) = (size.width as c_uint, size.height as c_uint);
+    let screen = unsafe { xlib::XDefaultScreen(dpy) };
+    let depth = unsafe { xlib::XDefaultDepth(dpy, screen) };
+
+    // ARGB values only work as pixel values on true color visuals
+    if (depth != 24 && depth != 32) || width == 0 || height == 0 {
+        return None;
+    }
+
+    // the mask has one bit per pixel, and its rows are padded to a byte
+    let mut argb = icon.to_argb();
+    let stride = size.width.div_ceil(8);
+    let mut mask = vec![0u8; stride * size.height];
+    for (i, px) in argb.iter().enumerate() {
+        if px >> 24 >= 0x80 {
+            let (x, y) = (i % size.width, i / size.width);
+            mask[y * stride + x / 8] |= 1 << (x % 8);
+        }
+    }
+
+    unsafe {
+        let image = xlib::XCreateImage(
+            dpy,
+            xlib::XDefaultVisual(dpy, screen),
+            depth as c_uint,
+            xlib::ZPixmap,
+            0,
+            argb.as_mut_ptr() as *mut c_char,
+            width,
+            height,
+            32,
+            0,
+        );
+        if image.is_null() {
+            return None;
+        }
+
+        // Xlib converts the pixels to the server's byte order if it needs to
+        (*image).byte_order = if cfg!(target_endian = "little") {
+            xlib::LSBFirst
+        } else {
+            xlib::MSBFirst
+        };
+
+        let pixmap = xlib::XCreatePixmap(dpy, window, width, height, depth as c_uint);
+        let gc = xlib::XCreateGC(dpy, pixmap, 0, ptr::null_mut());
+        xlib::XPutImage(dpy, pixmap, gc, image, 0, 0, 0, 0, width, height);
+        xlib::XFreeGC(dpy, gc);
+
+        // the pixels belong to the Vec, so keep XDestroyImage from freeing them
+        (*image).data = ptr::null_mut();
+        xlib::XDestroyImage(image);
+
+        let mask =
+            xlib::XCreateBitmapFromData(dpy, window, mask.as_ptr() as *const c_char, width, height);
+        Some((pixmap, mask))
+    }
+}
+
+// the shape in the standard cursor font closest to each cursor
+fn font_cursor_shape(cursor: &Cursor) -> Option<c_uint> {
+    Some(match cursor {
+        Cursor::Arrow => XC_LEFT_PTR,
+        Cursor::IBeam => XC_XTERM,
+        Cursor::Hand => XC_HAND2,
+        Cursor::Wait => XC_WATCH,
+        Cursor::Crosshair => XC_CROSSHAIR,
+        Cursor::ResizeHorizontal => XC_SB_H_DOUBLE_ARROW,
+        Cursor::ResizeVertical => XC_SB_V_DOUBLE_ARROW,
+        Cursor::ResizeNwSe => XC_BOTTOM_RIGHT_CORNER,
+        Cursor::ResizeNeSw => XC_BOTTOM_LEFT_CORNER,
+        Cursor::Move => XC_FLEUR,
+        Cursor::NotAllowed => XC_CIRCLE,
+        Cursor::Hidden | Cursor::Custom { .. } => return None,
+    })
+}
+
+// a cursor image for libXcursor, from Xcursor.h
+#[repr(C)]
+struct XcursorImage {
+    version: c_uint,
+    size: c_uint,
+    width: c_uint,
+    height: c_uint,
+    xhot: c_uint,
+    yhot: c_uint,
+    delay: c_uint,
+    pixels: *mut c_uint,
+}
+
+type XcursorImageCreate = unsafe extern "C" fn(c_int, c_int) -> *mut XcursorImage;
+type XcursorImageDestroy = unsafe extern "C" fn(*mut XcursorImage);
+type XcursorImageLoadCursor =
+    unsafe extern "C" fn(*mut xlib::Display, *const XcursorImage) -> xlib::Cursor;
+
+// the parts of libXcursor needed for full-color cursors. it isn't linked in, since it
+// isn't installed everywhere, so it's loaded the first time a custom cursor is made
+struct Xcursor {
+    image_create: XcursorImageCreate,
+    image_destroy: XcursorImageDestroy,
+    image_load_cursor: XcursorImageLoadCursor,
+}
+
+impl Xcursor {
+    unsafe fn load() -> Option<Self> {
+        let lib = libc::dlopen(
+            b"libXcursor.so.1\0".as_ptr() as *const libc::c_char,
+            libc::RTLD_LAZY,
+        );
+        if lib.is_null() {
+            log::debug!("libXcursor is unavailable, custom cursors will only have two colors");
+            return None;
+        }
+
+        let sym = |name: &[u8]| {
+            let sym = libc::dlsym(lib, name.as_ptr() as *const libc::c_char);
+            if sym.is_null() {
+                None
+            } else {
+                Some(sym)
+            }
+        };
+        let create = sym(b"XcursorImageCreate\0")?;
+        let destroy = sym(b"XcursorImageDestroy\0")?;
+        let load_cursor = sym(b"XcursorImageLoadCursor\0")?;
+        Some(Self {
+            image_create: mem::transmute::<*mut c_void, XcursorImageCreate>(create),
+            image_destroy: mem::transmute::<*mut c_void, XcursorImageDestroy>(destroy),
+            image_load_cursor: mem::transmute::<*mut c_void, XcursorImageLoadCursor>(load_cursor),
+        })
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref XCURSOR: Option<Xcursor> = unsafe { Xcursor::load() };
+}
+
+// create a full-color cursor from an image, if libXcursor is available
+fn argb_cursor(
+    dpy: *mut xlib::Display,
+    image: &PixelBuffer,
+    hotspot: Point2D<u32>,
+) -> Option<xlib::Cursor> {
+    let xcursor = XCURSOR.as_ref()?;
+    let size = image.size();
+    if size.area() == 0 {
+        return None;
+    }
+
+    unsafe {
+        let xc_image =
+            (xcursor.image_create)(size.width.try_into().ok()?, size.height.try_into().ok()?);
+        if xc_image.is_null() {
+            return None;
+        }
+
+        // the server rejects hotspots outside of the image
+        (*xc_image).xhot = hotspot.x.min((*xc_image).width - 1);
+        (*xc_image).yhot = hotspot.y.min((*xc_image).height - 1);
+
+        // Xcursor expects the color channels to be premultiplied by the alpha
+        let pixels = slice::from_raw_parts_mut((*xc_image).pixels, size.area());
+        for (dst, px) in pixels.iter_mut().zip(image.to_argb()) {
+            let alpha = px >> 24;
+            let channel = |shift: u32| (((px >> shift) & 0xFF) * alpha / 0xFF) << shift;
+            *dst = (alpha << 24) | channel(16) | channel(8) | channel(0);
+        }
+
+        let cursor = (xcursor.image_load_cursor)(dpy, xc_image);
+        (xcursor.image_destroy)(xc_image);
+        Some(cursor)
+    }
+}
+
+// create a two-color cursor from an image, or an invisible one if there's no image. this is
+// the fallback for when libXcursor isn't around
+// pixels that are at least half opaque are drawn, in black if they're dark and white otherwise
+fn pixmap_cursor(
+    dpy: *mut xlib::Display,
+    window: xlib::Window,
+    image: Option<(&PixelBuffer, Point2D<u32>)>,
+) -> xlib::Cursor {
+    let (size, argb) = match image {
+        Some((image, _)) => (image.size(), image.to_argb()),
+        None => (Size2D::new(1, 1), vec![0]),
+    };
+    let hotspot = image.map(|(_, hotspot)| hotspot).unwrap_or_default();
+
+    let stride = size.width.div_ceil(8);
+    let mut fg = vec![0u8; stride * size.height];
+    let mut mask = vec![0u8; stride * size.height];
+    for (i, px) in argb.iter().enumerate() {
+        let (x, y) = (i % size.width, i / size.width);
+        let bit = 1 << (x % 8);
+        let brightness = ((px >> 16) & 0xFF) + ((px >> 8) & 0xFF) + (px & 0xFF);
+        if px >> 24 >= 0x80 {
+            mask[y * stride + x / 8] |= bit;
+        }
+        if brightness < 0x180 {
+            fg[y * stride + x / 8] |= bit;
+        }
+    }
+
+    unsafe {
+        let (width, height) = (size.width as c_uint, size.height as c_uint);
+        let fg =
+            xlib::XCreateBitmapFromData(dpy, window, fg.as_ptr() as *const c_char, width, height);
+        let mask =
+            xlib::XCreateBitmapFromData(dpy, window, mask.as_ptr() as *const c_char, width, height);
+
+        let mut black: xlib::XColor = mem::zeroed();
+        let mut white: xlib::XColor = mem::zeroed();
+        white.red = 0xFFFF;
+        white.green = 0xFFFF;
+        white.blue = 0xFFFF;
+
+        // set bits in the foreground bitmap are drawn in the foreground color
+        let cursor = xlib::XCreatePixmapCursor(
+            dpy,
+            fg,
+            mask,
+            &mut black,
+            &mut white,
+            hotspot.x as c_uint,
+            hotspot.y as c_uint,
+        );
+        xlib::XFreePixmap(dpy, fg);
+        xlib::XFreePixmap(dpy, mask);
+        cursor
+    }
+}
+
+// add or remove _NET_WM_STATE atoms
+fn change_net_wm_state(
+    instance: &Instance,
+    window: xlib::Window,
+    add: bool,
+    states: &[&'static str],
+) -> crate::Result<()> {
+    let atoms = states
+        .iter()
+        .map(|s| instance.atom(s))
+        .collect::<crate::Result<SmallVec<[c_ulong; 2]>>>()?;
+
+    // until the window manager takes the window on, the property is set directly
+    if !is_managed(instance, window)? {
+        let mut current = instance.flutterbug_atom_property(window, "_NET_WM_STATE")?;
+        current.retain(|a| !atoms.contains(a));
+        if add {
+            current.extend(atoms);
+        }
+        return set_long_property(instance, window, "_NET_WM_STATE", xlib::XA_ATOM, &current);
+    }
+
+    // otherwise, the window manager has to be asked to do it
+    let mut ev: xlib::XClientMessageEvent = unsafe { mem::zeroed() };
+    ev.type_ = xlib::ClientMessage;
+    ev.window = window;
+    ev.message_type = instance.atom("_NET_WM_STATE")?;
+    ev.format = 32;
+    let first = atoms.first().copied().unwrap_or(0);
+    let second = atoms.get(1).copied().unwrap_or(0);
+    ev.data.set_long(0, if add { 1 } else { 0 });
+    ev.data.set_long(1, first as c_long);
+    ev.data.set_long(2, second as c_long);
+    ev.data.set_long(3, 1); // the request comes from an application
+
+    let dpy = instance.raw_display()?;
+    let mut ev = xlib::XEvent { client_message: ev };
+    unsafe {
+        xlib::XSendEvent(
+            dpy,
+            xlib::XDefaultRootWindow(dpy),
+            xlib::False,
+            xlib::SubstructureNotifyMask | xlib::SubstructureRedirectMask,
+            &mut ev,
+        );
+        xlib::XFlush(dpy);
+    }
+    Ok(())
+}
+
+// pass the size constraints on to the window manager
+fn set_normal_hints(
+    dpy: *mut xlib::Display,
+    window: xlib::Window,
+    constraints: &SizeConstraints,
+) -> crate::Result<()> {
+    let mut hints: xlib::XSizeHints = unsafe { mem::zeroed() };
+
+    // base size is what the increments are counted from
+    if let Some(min) = constraints.min() {
+        hints.flags |= xlib::PMinSize | xlib::PBaseSize;
+        hints.min_width = min.width.try_into()?;
+        hints.min_height = min.height.try_into()?;
+        hints.base_width = hints.min_width;
+        hints.base_height = hints.min_height;
+    }
+    if let Some(max) = constraints.max() {
+        hints.flags |= xlib::PMaxSize;
+        hints.max_width = max.width.try_into()?;
+        hints.max_height = max.height.try_into()?;
+    }
+    if let Some((w, h)) = constraints.aspect_ratio() {
+        hints.flags |= xlib::PAspect;
+        hints.min_aspect = xlib::AspectRatio {
+            x: w.try_into()?,
+            y: h.try_into()?,
+        };
+        hints.max_aspect = hints.min_aspect;
+    }
+    if let Some(inc) = constraints.increments() {
+        hints.flags |= xlib::PResizeInc;
+        hints.width_inc = inc.width.try_into()?;
+        hints.height_inc = inc.height.try_into()?;
+    }
+
+    unsafe { xlib::XSetWMNormalHints(dpy, window, &mut hints) };
+    Ok(())
+}
+
+// the constraints the window manager should enforce; the only portable way to stop the
+// user from resizing a window is to pin the minimum and maximum size to the current size
+#[inline]
+fn wm_constraints(
+    constraints: SizeConstraints,
+    resizable: bool,
+    size: Size2D<u32>,
+) -> SizeConstraints {
+    if resizable {
+        constraints
+    } else {
+        SizeConstraints::fixed(size)
+    }
+}
+
+// the events that every window needs, no matter which events it receives
+#[inline]
+fn base_event_mask() -> EventMask {
+    EventMask::EXPOSURE_MASK
+        | EventMask::FOCUS_CHANGE_MASK
+        | EventMask::STRUCTURE_NOTIFY_MASK
+        | EventMask::VISIBILITY_CHANGE_MASK
+        | EventMask::PROPERTY_CHANGE_MASK
+}
+
 pub struct WindowInternal {
     inner: FWindow,
     id: usize,
@@ -64,6 +601,26 @@ pub struct WindowInternal {
     ic: InputContext,
     top_level: bool,
     bounds: Rect<u32>,
+    motion_hint: bool,
+    constraints: SizeConstraints,
+    resizable: bool,
+    icon_pixmaps: Option<(xlib::Pixmap, xlib::Pixmap)>,
+    // the serial number of the request that last warped the pointer
+    warp_serial: AtomicUsize,
+}
+
+impl Drop for WindowInternal {
+    fn drop(&mut self) {
+        // unlike windows, pixmaps aren't freed along with the window they were made for
+        if let Some((pixmap, mask)) = self.icon_pixmaps.take() {
+            if let Ok(dpy) = self.inner.display_reference().raw() {
+                unsafe {
+                    xlib::XFreePixmap(dpy.as_ptr(), pixmap);
+                    xlib::XFreePixmap(dpy.as_ptr(), mask);
+                }
+            }
+        }
+    }
 }
 
 impl GenericWindowInternal for WindowInternal {
@@ -72,18 +629,22 @@ impl GenericWindowInternal for WindowInternal {
         self.id
     }
 
-    fn new(
-        instance: &Instance,
-        parent: Option<&Window>,
-        text: String,
-        bounds: Rect<u32>,
-        background: Option<Texture>,
-        top_level: bool,
-    ) -> crate::Result<Self> {
+    fn new(instance: &Instance, builder: WindowBuilder) -> crate::Result<Self> {
+        let WindowBuilder {
+            parent,
+            text,
+            bounds,
+            background,
+            style,
+            window_type,
+            ..
+        } = builder;
+        let top_level = parent.is_none();
+
         // create the struct representing the internal flutterbug window
         let dpy = instance.display();
         // TODO: let's not duplicate code here
-        let mut inner_win = parent.map(|p| p.inner_window());
+        let mut inner_win = parent.as_ref().map(|p| p.inner_window());
 
         let inner = dpy.create_simple_window(
             match inner_win {
@@ -98,9 +659,25 @@ impl GenericWindowInternal for WindowInternal {
             dpy.default_white_pixel()?,
         )?;
 
-        inner.set_protocols(&mut [instance.delete_window_atom()])?;
+        let mut protocols = crate::instance::WM_PROTOCOLS
+            .iter()
+            .map(|name| instance.atom(name))
+            .collect::<crate::Result<SmallVec<[Atom; 3]>>>()?;
+        inner.set_protocols(&mut protocols)?;
         inner.store_name(&text)?;
-        inner.select_input(EventMask::EXPOSURE_MASK)?;
+        inner.select_input(base_event_mask())?;
+
+        // the window manager reads these properties when the window is first mapped
+        let resizable = style.contains(WindowStyle::RESIZABLE);
+        let constraints = SizeConstraints::new();
+        if top_level {
+            set_window_style(instance, inner.window(), style, window_type)?;
+            set_normal_hints(
+                instance.raw_display()?,
+                inner.window(),
+                &wm_constraints(constraints, resizable, bounds.size),
+            )?;
+        }
 
         Ok(WindowInternal {
             id: unique_id(),
@@ -111,6 +688,11 @@ impl GenericWindowInternal for WindowInternal {
             ic: inner.input_context(instance.im())?,
             inner,
             top_level,
+            motion_hint: false,
+            constraints,
+            resizable,
+            icon_pixmaps: None,
+            warp_serial: AtomicUsize::new(0),
         })
     }
 
@@ -150,6 +732,16 @@ impl GenericWindowInternal for WindowInternal {
 
     fn set_bounds(&mut self, bounds: Rect<u32>, backend: bool) -> crate::Result<Rect<u32>> {
         if backend {
+            // move the pin first, or the window manager will undo the resize
+            if !self.resizable && self.top_level && bounds.size != self.bounds.size {
+                let dpy = self.inner.display_reference().raw()?.as_ptr();
+                set_normal_hints(
+                    dpy,
+                    self.inner.window(),
+                    &SizeConstraints::fixed(bounds.size),
+                )?;
+            }
+
             self.inner.set_bounds(
                 Point2D::new(bounds.origin.x.try_into()?, bounds.origin.y.try_into()?),
                 bounds.size,
@@ -166,16 +758,64 @@ impl GenericWindowInternal for WindowInternal {
         Ok(self.inner.map(true)?)
     }
 
+    #[inline]
+    fn focus(&self) -> crate::Result<()> {
+        let dpy = self.inner.display_reference().raw()?.as_ptr();
+        unsafe {
+            xlib::XSetInputFocus(
+                dpy,
+                self.inner.window(),
+                xlib::RevertToParent,
+                xlib::CurrentTime,
+            )
+        };
+        Ok(())
+    }
+
+    #[inline]
+    fn destroy(&self) -> crate::Result<()> {
+        // the DestroyNotify that the server sends back takes care of the rest
+        let dpy = self.inner.display_reference().raw()?.as_ptr();
+        unsafe {
+            xlib::XDestroyWindow(dpy, self.inner.window());
+            xlib::XFlush(dpy);
+        }
+        Ok(())
+    }
+
     fn receive_events(&self, events: &[EventType]) -> crate::Result<()> {
         // figure out which events correspond to which X11 event masks
         lazy_static::lazy_static! {
             static ref X11_EVENT_MAPPING: HashMap<EventType, SmallVec<[EventMask; 1]>> = {
                 let mut map = HashMap::new();
                 map.insert(EventType::KeyDown, smallvec![EventMask::KEY_PRESS_MASK]);
+                map.insert(EventType::TextInput, smallvec![EventMask::KEY_PRESS_MASK]);
                 map.insert(EventType::KeyUp, smallvec![EventMask::KEY_RELEASE_MASK]);
 
                 map.insert(EventType::MouseButtonDown, smallvec![EventMask::BUTTON_PRESS_MASK]);
                 map.insert(EventType::MouseButtonUp, smallvec![EventMask::BUTTON_RELEASE_MASK]);
+                map.insert(EventType::Scroll, smallvec![EventMask::BUTTON_PRESS_MASK]);
+
+                map.insert(EventType::MouseMove, smallvec![EventMask::POINTER_MOTION_MASK]);
+                map.insert(EventType::MouseDelta, smallvec![EventMask::POINTER_MOTION_MASK]);
+                map.insert(EventType::MouseEnter, smallvec![EventMask::ENTER_WINDOW_MASK]);
+                map.insert(EventType::MouseLeave, smallvec![EventMask::LEAVE_WINDOW_MASK]);
+
+                map.insert(
+                    EventType::Click,
+                    smallvec![EventMask::BUTTON_PRESS_MASK, EventMask::BUTTON_RELEASE_MASK]
+                );
+                let drag_mask = smallvec![
+                    EventMask::BUTTON_PRESS_MASK,
+                    EventMask::BUTTON_RELEASE_MASK,
+                    EventMask::BUTTON_MOTION_MASK
+                ];
+                map.insert(EventType::DragStart, drag_mask.clone());
+                map.insert(EventType::DragMove, drag_mask.clone());
+                map.insert(EventType::DragEnd, drag_mask);
+
+                map.insert(EventType::FocusIn, smallvec![EventMask::FOCUS_CHANGE_MASK]);
+                map.insert(EventType::FocusOut, smallvec![EventMask::FOCUS_CHANGE_MASK]);
 
                 // TODO: add more events
                 map
@@ -196,11 +836,17 @@ impl GenericWindowInternal for WindowInternal {
             return Ok(()); // TODO: maybe undefined behavior if this function is called more than once?
         }
 
-        let mut sum_event_mask = EventMask::EXPOSURE_MASK; // EXPOSURE_MASK is there no matter what
+        let mut sum_event_mask = base_event_mask(); // the base events are there no matter what
         for e in event_set {
             sum_event_mask |= e;
         }
 
+        // motion hints tell the server to only send one motion event until we ask for
+        // the pointer position again, which prevents floods of motion events
+        if self.motion_hint && sum_event_mask.contains(EventMask::POINTER_MOTION_MASK) {
+            sum_event_mask |= EventMask::POINTER_MOTION_HINT_MASK;
+        }
+
         Ok(self.inner.select_input(sum_event_mask)?)
     }
 
@@ -240,6 +886,208 @@ impl GenericWindowInternal for WindowInternal {
     fn take_background(&mut self) -> Option<Texture> {
         self.background.take()
     }
+
+    #[inline]
+    fn set_motion_hint(&mut self, hint: bool) {
+        self.motion_hint = hint;
+    }
+
+    fn set_state(&self, instance: &Instance, state: WindowState) -> crate::Result<()> {
+        let dpy = instance.raw_display()?;
+        let window = self.inner.window();
+        let managed = is_managed(instance, window)?;
+
+        match state {
+            WindowState::Minimized if managed => unsafe {
+                xlib::XIconifyWindow(dpy, window, xlib::XDefaultScreen(dpy));
+                xlib::XFlush(dpy);
+            },
+            WindowState::Minimized => set_initial_state(dpy, window, ICONIC_STATE),
+            WindowState::Normal | WindowState::Maximized => {
+                // mapping an iconified window is how it's restored. this does nothing if
+                // the window is already mapped
+                if managed {
+                    unsafe { xlib::XMapWindow(dpy, window) };
+                } else {
+                    set_initial_state(dpy, window, NORMAL_STATE);
+                }
+
+                change_net_wm_state(
+                    instance,
+                    window,
+                    state == WindowState::Maximized,
+                    &[
+                        "_NET_WM_STATE_MAXIMIZED_VERT",
+                        "_NET_WM_STATE_MAXIMIZED_HORZ",
+                    ],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn set_fullscreen(&mut self, instance: &Instance, fullscreen: bool) -> crate::Result<()> {
+        change_net_wm_state(
+            instance,
+            self.inner.window(),
+            fullscreen,
+            &["_NET_WM_STATE_FULLSCREEN"],
+        )
+    }
+
+    #[inline]
+    fn set_always_on_top(&self, instance: &Instance, above: bool) -> crate::Result<()> {
+        change_net_wm_state(
+            instance,
+            self.inner.window(),
+            above,
+            &["_NET_WM_STATE_ABOVE"],
+        )
+    }
+
+    fn set_icon(&mut self, instance: &Instance, icons: &[PixelBuffer]) -> crate::Result<()> {
+        let dpy = instance.raw_display()?;
+        let window = self.inner.window();
+
+        // _NET_WM_ICON holds the width, height and ARGB pixels of every size in a row
+        if icons.is_empty() {
+            unsafe { xlib::XDeleteProperty(dpy, window, instance.atom("_NET_WM_ICON")?) };
+        } else {
+            let data = icons
+                .iter()
+                .flat_map(|icon| {
+                    let size = icon.size();
+                    iter::once(size.width as c_ulong)
+                        .chain(iter::once(size.height as c_ulong))
+                        .chain(icon.to_argb().into_iter().map(|px| px as c_ulong))
+                })
+                .collect::<Vec<c_ulong>>();
+            set_long_property(instance, window, "_NET_WM_ICON", xlib::XA_CARDINAL, &data)?;
+        }
+
+        // older window managers only look at the icon pixmap in WM_HINTS
+        let distance = |icon: &&PixelBuffer| {
+            let width = icon.size().width;
+            width.max(LEGACY_ICON_SIZE) - width.min(LEGACY_ICON_SIZE)
+        };
+        let pixmaps = icons
+            .iter()
+            .min_by_key(distance)
+            .and_then(|icon| icon_pixmaps(dpy, window, icon));
+        update_wm_hints(dpy, window, |hints| match pixmaps {
+            Some((pixmap, mask)) => {
+                hints.flags |= xlib::IconPixmapHint | xlib::IconMaskHint;
+                hints.icon_pixmap = pixmap;
+                hints.icon_mask = mask;
+            }
+            None => hints.flags &= !(xlib::IconPixmapHint | xlib::IconMaskHint),
+        });
+
+        if let Some((pixmap, mask)) = mem::replace(&mut self.icon_pixmaps, pixmaps) {
+            unsafe {
+                xlib::XFreePixmap(dpy, pixmap);
+                xlib::XFreePixmap(dpy, mask);
+            }
+        }
+        unsafe { xlib::XFlush(dpy) };
+        Ok(())
+    }
+
+    fn set_cursor(&mut self, instance: &Instance, cursor: &Cursor) -> crate::Result<()> {
+        let dpy = instance.raw_display()?;
+        let window = self.inner.window();
+
+        match font_cursor_shape(cursor) {
+            // font cursors are cached by the instance, so they outlive the window
+            Some(shape) => {
+                let cursor = instance.flutterbug_font_cursor(shape)?;
+                unsafe { xlib::XDefineCursor(dpy, window, cursor) };
+            }
+            // the server keeps its own reference to a defined cursor, so ours can be freed
+            None => {
+                let cursor = match cursor {
+                    Cursor::Custom { image, hotspot } => argb_cursor(dpy, image, *hotspot)
+                        .unwrap_or_else(|| pixmap_cursor(dpy, window, Some((&**image, *hotspot)))),
+                    _ => pixmap_cursor(dpy, window, None),
+                };
+                unsafe {
+                    xlib::XDefineCursor(dpy, window, cursor);
+                    xlib::XFreeCursor(dpy, cursor);
+                }
+            }
+        }
+
+        unsafe { xlib::XFlush(dpy) };
+        Ok(())
+    }
+
+    fn grab_pointer(&self, instance: &Instance, confine: bool) -> crate::Result<()> {
+        let dpy = instance.raw_display()?;
+        let window = self.inner.window();
+        let mask = xlib::ButtonPressMask | xlib::ButtonReleaseMask | xlib::PointerMotionMask;
+        let confine_to = if confine { window } else { 0 };
+
+        // with owner_events set, events over our other windows still go to those windows
+        let res = unsafe {
+            xlib::XGrabPointer(
+                dpy,
+                window,
+                xlib::True,
+                mask as c_uint,
+                xlib::GrabModeAsync,
+                xlib::GrabModeAsync,
+                confine_to,
+                0,
+                xlib::CurrentTime,
+            )
+        };
+
+        // grabs fail if the window isn't viewable or another client has the pointer
+        if res == xlib::GrabSuccess {
+            Ok(())
+        } else {
+            Err(crate::Error::StaticMsg("Unable to grab the pointer"))
+        }
+    }
+
+    fn release_pointer(&self, instance: &Instance) -> crate::Result<()> {
+        let dpy = instance.raw_display()?;
+        unsafe {
+            xlib::XUngrabPointer(dpy, xlib::CurrentTime);
+            xlib::XFlush(dpy);
+        }
+        Ok(())
+    }
+
+    fn warp_pointer(&self, instance: &Instance, location: Point2D<i32>) -> crate::Result<()> {
+        let dpy = instance.raw_display()?;
+        let window = self.inner.window();
+        unsafe {
+            let serial = xlib::XNextRequest(dpy) as usize;
+            self.warp_serial.store(serial, Ordering::Release);
+            xlib::XWarpPointer(dpy, 0, window, 0, 0, 0, 0, location.x, location.y);
+            xlib::XFlush(dpy);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn size_constraints(&self) -> SizeConstraints {
+        self.constraints
+    }
+
+    fn set_size_constraints(&mut self, constraints: SizeConstraints) -> crate::Result<()> {
+        let dpy = self.inner.display_reference().raw()?.as_ptr();
+        set_normal_hints(
+            dpy,
+            self.inner.window(),
+            &wm_constraints(constraints, self.resizable, self.bounds.size),
+        )?;
+        self.constraints = constraints;
+        Ok(())
+    }
 }
 
 impl WindowInternal {
@@ -248,6 +1096,13 @@ impl WindowInternal {
         &self.inner
     }
 
+    /// Tell if an event with the given serial number was generated before the pointer
+    /// was last warped.
+    #[inline]
+    pub(crate) fn is_before_warp(&self, serial: c_ulong) -> bool {
+        (serial as usize) < self.warp_serial.load(Ordering::Acquire)
+    }
+
     /// Get the input context.
     pub fn ic(&self) -> &InputContext {
         &self.ic
@@ -43,10 +43,12 @@
  * ----------------------------------------------------------------------------------
  */
 
-pub(crate) use super::{unique_id, Window};
-use crate::{Event, EventType, Instance, Texture};
+pub(crate) use super::{unique_id, WindowBuilder};
+use crate::{
+    Cursor, Event, EventType, Instance, PixelBuffer, SizeConstraints, Texture, WindowState,
+};
 use alloc::string::String;
-use euclid::default::Rect;
+use euclid::default::{Point2D, Rect};
 
 #[cfg(target_os = "linux")]
 mod flutter;
@@ -71,14 +73,7 @@ pub trait GenericWindowInternal: Sized {
     fn id(&self) -> usize;
 
     /// Create a new version of this window.
-    fn new(
-        instance: &Instance,
-        parent: Option<&Window>,
-        text: String,
-        bounds: Rect<u32>,
-        background: Option<Texture>,
-        top_level: bool,
-    ) -> crate::Result<Self>;
+    fn new(instance: &Instance, builder: WindowBuilder) -> crate::Result<Self>;
 
     /// Respond to an event.
     #[inline]
@@ -90,6 +85,11 @@ pub trait GenericWindowInternal: Sized {
     /// Receive certain types of events.
     fn receive_events(&self, events: &[EventType]) -> crate::Result<()>;
 
+    /// Set whether or not mouse motion should be reported using motion hints. This takes
+    /// effect the next time `receive_events` is called.
+    #[inline]
+    fn set_motion_hint(&mut self, _hint: bool) {}
+
     /// Get the current event handler.
     fn event_handler(&self) -> &dyn EventHandler;
 
@@ -104,12 +104,15 @@ pub trait GenericWindowInternal: Sized {
     fn set_text(&mut self, txt: String) -> crate::Result<String>;
 
     /// Get the texture used for the background of this window.
+    #[allow(dead_code)]
     fn background(&self) -> Option<&Texture>;
 
     /// Set the texture used for the background of this window.
+    #[allow(dead_code)]
     fn set_background(&mut self, texture: Option<Texture>);
 
     /// Take the background.
+    #[allow(dead_code)]
     fn take_background(&mut self) -> Option<Texture>;
 
     /// Get the bounds of this window.
@@ -124,6 +127,42 @@ pub trait GenericWindowInternal: Sized {
     /// Display the window.
     fn show(&self) -> crate::Result<()>;
 
+    /// Give the keyboard focus to this window.
+    fn focus(&self) -> crate::Result<()>;
+
+    /// Minimize, maximize or restore this window.
+    fn set_state(&self, instance: &Instance, state: WindowState) -> crate::Result<()>;
+
+    /// Make this window cover the entire screen, or return it to normal.
+    fn set_fullscreen(&mut self, instance: &Instance, fullscreen: bool) -> crate::Result<()>;
+
+    /// Keep this window above other windows, or stop doing so.
+    fn set_always_on_top(&self, instance: &Instance, above: bool) -> crate::Result<()>;
+
+    /// Set the icon of this window, given several sizes of the same image.
+    fn set_icon(&mut self, instance: &Instance, icons: &[PixelBuffer]) -> crate::Result<()>;
+
+    /// Set the cursor shown while the mouse is over this window.
+    fn set_cursor(&mut self, instance: &Instance, cursor: &Cursor) -> crate::Result<()>;
+
+    /// Send all mouse events to this window, optionally keeping the pointer inside of it.
+    fn grab_pointer(&self, instance: &Instance, confine: bool) -> crate::Result<()>;
+
+    /// Undo a previous pointer grab.
+    fn release_pointer(&self, instance: &Instance) -> crate::Result<()>;
+
+    /// Move the pointer to a location relative to this window.
+    fn warp_pointer(&self, instance: &Instance, location: Point2D<i32>) -> crate::Result<()>;
+
+    /// Get the limits on the size of this window.
+    fn size_constraints(&self) -> SizeConstraints;
+
+    /// Set the limits on the size of this window.
+    fn set_size_constraints(&mut self, constraints: SizeConstraints) -> crate::Result<()>;
+
+    /// Ask the backend to destroy this window.
+    fn destroy(&self) -> crate::Result<()>;
+
     /// Repaint the window.
     fn repaint(&self, bounds: Option<Rect<u32>>) -> crate::Result<()>;
 }
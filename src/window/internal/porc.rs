@@ -43,24 +43,33 @@
  * ----------------------------------------------------------------------------------
  */
 
-use super::{super::Window, unique_id, EventHandler, GenericWindowInternal};
-use crate::{take_vec::TakeVec, Event, EventType, Instance, Texture};
+use super::{unique_id, EventHandler, GenericWindowInternal, WindowBuilder};
+use crate::{
+    take_vec::TakeVec, Cursor, Event, EventType, Instance, PixelBuffer, SizeConstraints, Texture,
+    WindowState,
+};
 use alloc::{
     boxed::Box,
     string::{String, ToString},
     sync::Arc,
 };
-use core::{any::Any, convert::TryInto, mem, sync::atomic::AtomicPtr};
+use core::{
+    any::Any,
+    convert::TryInto,
+    mem, ptr,
+    sync::atomic::{AtomicBool, AtomicPtr, Ordering},
+};
 use cty::c_int;
-use euclid::default::Rect;
+use euclid::default::{Point2D, Rect};
 use porcupine::{
     prelude::*,
     winapi::{
         shared::{
-            minwindef::{LPARAM, UINT, WPARAM},
-            windef::HWND,
+            basetsd::LONG_PTR,
+            minwindef::{BOOL, FALSE, LPARAM, TRUE, UINT, WPARAM},
+            windef::{HCURSOR, HICON, HWND, POINT, RECT},
         },
-        um::winuser,
+        um::{wingdi, winuser},
     },
     CmdShow, ExtendedWindowStyle, OwnedWindowClass, Window as PWindow, WindowClass, WindowStyle,
 };
@@ -85,6 +94,43 @@ impl WindowClass for BEETLE_WINDOW_CLASS {
     }
 }
 
+// translate Beetle's window style into Win32 window styles
+fn window_styles(
+    style: crate::WindowStyle,
+    window_type: crate::WindowType,
+) -> (WindowStyle, ExtendedWindowStyle) {
+    let mut ws = WindowStyle::CLIP_CHILDREN | WindowStyle::MINIMIZE_BOX;
+    let mut ex_ws = ExtendedWindowStyle::CLIENT_EDGE;
+
+    if style.contains(crate::WindowStyle::DECORATED) {
+        ws |= WindowStyle::CAPTION;
+    } else {
+        ws |= WindowStyle::POPUP;
+    }
+    if style.contains(crate::WindowStyle::CLOSABLE) {
+        ws |= WindowStyle::SYSMENU;
+    }
+    if style.contains(crate::WindowStyle::RESIZABLE) {
+        ws |= WindowStyle::SIZEBOX | WindowStyle::MAXIMIZE_BOX;
+    }
+    if style.contains(crate::WindowStyle::ALWAYS_ON_TOP) {
+        ex_ws |= ExtendedWindowStyle::TOPMOST;
+    }
+
+    // tool windows are left out of the taskbar
+    match window_type {
+        crate::WindowType::Utility | crate::WindowType::Tooltip => {
+            ex_ws |= ExtendedWindowStyle::TOOL_WINDOW
+        }
+        _ if style.contains(crate::WindowStyle::SKIP_TASKBAR) => {
+            ex_ws |= ExtendedWindowStyle::TOOL_WINDOW
+        }
+        _ => (),
+    }
+
+    (ws, ex_ws)
+}
+
 pub struct WindowInternal {
     id: usize,
     inner: PWindow,
@@ -96,6 +142,79 @@ pub struct WindowInternal {
 
     // storage for old bounds for size change events
     old_bounds: TakeVec<Rect<u32>>,
+
+    // enforced by the window procedure, in WM_SIZING
+    constraints: SizeConstraints,
+    resizable: bool,
+
+    // the style and bounds to go back to when leaving fullscreen
+    windowed: Option<(LONG_PTR, RECT)>,
+
+    // the large and small icons, which we have to destroy ourselves
+    icons: [HICON; 2],
+
+    // set by the window procedure, in WM_SETCURSOR; custom cursors are ours to destroy
+    cursor: HCURSOR,
+    custom_cursor: bool,
+
+    // whether the pointer is kept inside of the client area, which moves with the window
+    confined: AtomicBool,
+}
+
+impl Drop for WindowInternal {
+    fn drop(&mut self) {
+        destroy_icons(&self.icons);
+        if self.custom_cursor {
+            unsafe { winuser::DestroyCursor(self.cursor) };
+        }
+    }
+}
+
+// create an icon out of a pixel buffer, or a cursor if it has a hotspot
+fn create_icon(icon: &PixelBuffer, hotspot: Option<Point2D<u32>>) -> HICON {
+    let size = icon.size();
+    let argb = icon.to_argb();
+
+    unsafe {
+        // with a 32-bit color bitmap, the alpha channel is used instead of the mask
+        let color = wingdi::CreateBitmap(
+            size.width as c_int,
+            size.height as c_int,
+            1,
+            32,
+            argb.as_ptr() as *const _,
+        );
+        let mask =
+            wingdi::CreateBitmap(size.width as c_int, size.height as c_int, 1, 1, ptr::null());
+
+        let mut info = winuser::ICONINFO {
+            fIcon: if hotspot.is_some() { FALSE } else { TRUE } as BOOL,
+            xHotspot: hotspot.map(|h| h.x).unwrap_or(0),
+            yHotspot: hotspot.map(|h| h.y).unwrap_or(0),
+            hbmMask: mask,
+            hbmColor: color,
+        };
+        let hicon = winuser::CreateIconIndirect(&mut info);
+
+        wingdi::DeleteObject(color as *mut _);
+        wingdi::DeleteObject(mask as *mut _);
+        hicon
+    }
+}
+
+#[inline]
+fn cursor_is_custom(cursor: &Cursor) -> bool {
+    matches!(cursor, Cursor::Custom { .. })
+}
+
+#[inline]
+fn destroy_icons(icons: &[HICON]) {
+    icons
+        .iter()
+        .filter(|icon| !icon.is_null())
+        .for_each(|icon| unsafe {
+            winuser::DestroyIcon(*icon);
+        });
 }
 
 impl GenericWindowInternal for WindowInternal {
@@ -104,27 +223,38 @@ impl GenericWindowInternal for WindowInternal {
         self.id
     }
 
-    fn new(
-        instance: &Instance,
-        parent: Option<&Window>,
-        text: String,
-        bounds: Rect<u32>,
-        background: Option<Texture>,
-        is_top_level: bool,
-    ) -> crate::Result<Self> {
-        let default_ws = WindowStyle::CLIP_CHILDREN
-            | WindowStyle::SYSMENU
-            | WindowStyle::SIZEBOX
-            | WindowStyle::MINIMIZE_BOX
-            | WindowStyle::CAPTION;
+    fn new(instance: &Instance, builder: WindowBuilder) -> crate::Result<Self> {
+        let WindowBuilder {
+            parent,
+            text,
+            bounds,
+            background,
+            style,
+            window_type,
+            ..
+        } = builder;
+        let is_top_level = parent.is_none();
+
+        let (ws, ex_ws) = if is_top_level {
+            window_styles(style, window_type)
+        } else {
+            (
+                WindowStyle::CLIP_CHILDREN
+                    | WindowStyle::SYSMENU
+                    | WindowStyle::SIZEBOX
+                    | WindowStyle::MINIMIZE_BOX
+                    | WindowStyle::CAPTION,
+                ExtendedWindowStyle::CLIENT_EDGE,
+            )
+        };
 
         // create internal window
-        let l = parent.map(|p| p.inner_window());
+        let l = parent.as_ref().map(|p| p.inner_window());
         let pw = PWindow::with_creation_param(
             &BEETLE_WINDOW_CLASS,
             &text,
-            default_ws,
-            ExtendedWindowStyle::CLIENT_EDGE,
+            ws,
+            ex_ws,
             euclid::rect(
                 bounds.origin.x.try_into()?,
                 bounds.origin.y.try_into()?,
@@ -136,7 +266,7 @@ impl GenericWindowInternal for WindowInternal {
                 Some(Err(e)) => return Err(e),
                 Some(Ok(ref l)) => Some(l.inner_porc_window()),
             },
-            Some(Box::new(instance.clone())), // pass the instance to WM_NCCREATE
+            Some(Box::new(instance.downgrade())), // pass the instance to WM_NCCREATE
         )?;
 
         Ok(Self {
@@ -148,6 +278,13 @@ impl GenericWindowInternal for WindowInternal {
             top_level: is_top_level,
             bounds,
             old_bounds: TakeVec::new(),
+            windowed: None,
+            icons: [ptr::null_mut(); 2],
+            cursor: unsafe { winuser::LoadCursorW(ptr::null_mut(), winuser::IDC_ARROW) },
+            custom_cursor: false,
+            confined: AtomicBool::new(false),
+            constraints: SizeConstraints::new(),
+            resizable: style.contains(crate::WindowStyle::RESIZABLE),
         })
     }
 
@@ -210,6 +347,9 @@ impl GenericWindowInternal for WindowInternal {
                 bounds.size.width.try_into()?,
                 bounds.size.height.try_into()?,
             ))?;
+
+            // the window procedure can't get at the window while it's being reshaped
+            self.update_cursor_clip();
         }
 
         let mut res = bounds;
@@ -228,6 +368,204 @@ impl GenericWindowInternal for WindowInternal {
         unimplemented!()
     }
 
+    #[inline]
+    fn focus(&self) -> crate::Result<()> {
+        unsafe { winuser::SetFocus(self.inner.hwnd().as_ptr()) };
+        Ok(())
+    }
+
+    #[inline]
+    fn set_state(&self, _instance: &Instance, state: WindowState) -> crate::Result<()> {
+        let cmd = match state {
+            WindowState::Normal => winuser::SW_RESTORE,
+            WindowState::Minimized => winuser::SW_MINIMIZE,
+            WindowState::Maximized => winuser::SW_MAXIMIZE,
+        };
+        unsafe { winuser::ShowWindow(self.inner.hwnd().as_ptr(), cmd) };
+        Ok(())
+    }
+
+    fn set_fullscreen(&mut self, _instance: &Instance, fullscreen: bool) -> crate::Result<()> {
+        let hwnd = self.inner.hwnd().as_ptr();
+        let frame_styles = (winuser::WS_OVERLAPPEDWINDOW | winuser::WS_POPUP) as LONG_PTR;
+
+        match (fullscreen, self.windowed) {
+            (true, None) => unsafe {
+                // remember how the window looked before
+                let style = winuser::GetWindowLongPtrA(hwnd, winuser::GWL_STYLE);
+                let mut rect: RECT = mem::zeroed();
+                winuser::GetWindowRect(hwnd, &mut rect);
+                self.windowed = Some((style, rect));
+
+                // cover the monitor that the window is mostly on
+                let monitor = winuser::MonitorFromWindow(hwnd, winuser::MONITOR_DEFAULTTONEAREST);
+                let mut mi: winuser::MONITORINFO = mem::zeroed();
+                mi.cbSize = mem::size_of::<winuser::MONITORINFO>() as _;
+                winuser::GetMonitorInfoA(monitor, &mut mi);
+                let r = mi.rcMonitor;
+
+                winuser::SetWindowLongPtrA(
+                    hwnd,
+                    winuser::GWL_STYLE,
+                    (style & !frame_styles) | winuser::WS_POPUP as LONG_PTR,
+                );
+                winuser::SetWindowPos(
+                    hwnd,
+                    winuser::HWND_TOP,
+                    r.left,
+                    r.top,
+                    r.right - r.left,
+                    r.bottom - r.top,
+                    winuser::SWP_FRAMECHANGED | winuser::SWP_NOOWNERZORDER,
+                );
+            },
+            (false, Some((style, r))) => unsafe {
+                self.windowed = None;
+                winuser::SetWindowLongPtrA(hwnd, winuser::GWL_STYLE, style);
+                winuser::SetWindowPos(
+                    hwnd,
+                    ptr::null_mut(),
+                    r.left,
+                    r.top,
+                    r.right - r.left,
+                    r.bottom - r.top,
+                    winuser::SWP_FRAMECHANGED | winuser::SWP_NOZORDER | winuser::SWP_NOOWNERZORDER,
+                );
+            },
+            _ => { /* already in the requested mode */ }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn set_always_on_top(&self, _instance: &Instance, above: bool) -> crate::Result<()> {
+        let after = if above {
+            winuser::HWND_TOPMOST
+        } else {
+            winuser::HWND_NOTOPMOST
+        };
+        unsafe {
+            winuser::SetWindowPos(
+                self.inner.hwnd().as_ptr(),
+                after,
+                0,
+                0,
+                0,
+                0,
+                winuser::SWP_NOMOVE | winuser::SWP_NOSIZE | winuser::SWP_NOACTIVATE,
+            )
+        };
+        Ok(())
+    }
+
+    fn set_icon(&mut self, _instance: &Instance, icons: &[PixelBuffer]) -> crate::Result<()> {
+        let hwnd = self.inner.hwnd().as_ptr();
+
+        // the large icon is used by the taskbar and Alt+Tab, the small one by the title bar
+        let large = icons.iter().max_by_key(|icon| icon.size().width);
+        let small = icons.iter().min_by_key(|icon| icon.size().width);
+        let to_icon = |icon: &PixelBuffer| create_icon(icon, None);
+        let new_icons = [
+            large.map(to_icon).unwrap_or(ptr::null_mut()),
+            small.map(to_icon).unwrap_or(ptr::null_mut()),
+        ];
+
+        for (kind, icon) in [winuser::ICON_BIG, winuser::ICON_SMALL]
+            .iter()
+            .zip(new_icons.iter())
+        {
+            unsafe {
+                winuser::SendMessageA(hwnd, winuser::WM_SETICON, *kind as WPARAM, *icon as LPARAM)
+            };
+        }
+
+        destroy_icons(&mem::replace(&mut self.icons, new_icons));
+        Ok(())
+    }
+
+    fn set_cursor(&mut self, _instance: &Instance, cursor: &Cursor) -> crate::Result<()> {
+        let system = |name| unsafe { winuser::LoadCursorW(ptr::null_mut(), name) };
+        let new_cursor = match cursor {
+            Cursor::Arrow => system(winuser::IDC_ARROW),
+            Cursor::IBeam => system(winuser::IDC_IBEAM),
+            Cursor::Hand => system(winuser::IDC_HAND),
+            Cursor::Wait => system(winuser::IDC_WAIT),
+            Cursor::Crosshair => system(winuser::IDC_CROSS),
+            Cursor::ResizeHorizontal => system(winuser::IDC_SIZEWE),
+            Cursor::ResizeVertical => system(winuser::IDC_SIZENS),
+            Cursor::ResizeNwSe => system(winuser::IDC_SIZENWSE),
+            Cursor::ResizeNeSw => system(winuser::IDC_SIZENESW),
+            Cursor::Move => system(winuser::IDC_SIZEALL),
+            Cursor::NotAllowed => system(winuser::IDC_NO),
+            // a null cursor hides it
+            Cursor::Hidden => ptr::null_mut(),
+            Cursor::Custom { image, hotspot } => create_icon(image, Some(*hotspot)),
+        };
+
+        let old_cursor = mem::replace(&mut self.cursor, new_cursor);
+        let was_custom = mem::replace(&mut self.custom_cursor, cursor_is_custom(cursor));
+
+        // WM_SETCURSOR only comes when the mouse moves, so update a cursor that's already over us
+        let hwnd = self.inner.hwnd().as_ptr();
+        let mut point = POINT { x: 0, y: 0 };
+        unsafe {
+            if winuser::GetCursorPos(&mut point) != 0 && winuser::WindowFromPoint(point) == hwnd {
+                winuser::SetCursor(new_cursor);
+            }
+            if was_custom {
+                winuser::DestroyCursor(old_cursor);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn grab_pointer(&self, _instance: &Instance, confine: bool) -> crate::Result<()> {
+        unsafe { winuser::SetCapture(self.inner.hwnd().as_ptr()) };
+        self.confined.store(confine, Ordering::Release);
+        self.update_cursor_clip();
+        Ok(())
+    }
+
+    fn release_pointer(&self, _instance: &Instance) -> crate::Result<()> {
+        self.confined.store(false, Ordering::Release);
+        unsafe {
+            winuser::ClipCursor(ptr::null());
+            winuser::ReleaseCapture();
+        }
+        Ok(())
+    }
+
+    fn warp_pointer(&self, _instance: &Instance, location: Point2D<i32>) -> crate::Result<()> {
+        let mut point = POINT {
+            x: location.x,
+            y: location.y,
+        };
+        unsafe {
+            winuser::ClientToScreen(self.inner.hwnd().as_ptr(), &mut point);
+            winuser::SetCursorPos(point.x, point.y);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn size_constraints(&self) -> SizeConstraints {
+        self.constraints
+    }
+
+    #[inline]
+    fn set_size_constraints(&mut self, constraints: SizeConstraints) -> crate::Result<()> {
+        self.constraints = constraints;
+        Ok(())
+    }
+
+    #[inline]
+    fn destroy(&self) -> crate::Result<()> {
+        unsafe { winuser::DestroyWindow(self.inner.hwnd().as_ptr()) };
+        Ok(())
+    }
+
     #[inline]
     fn repaint(&self, bounds: Option<Rect<u32>>) -> crate::Result<()> {
         self.inner.repaint();
@@ -255,6 +593,11 @@ impl WindowInternal {
         Ok(self.inner.set_user_data_box(boxed)?)
     }
 
+    #[inline]
+    pub(crate) fn cursor(&self) -> HCURSOR {
+        self.cursor
+    }
+
     #[inline]
     pub(crate) fn store_old_bounds(&mut self) {
         self.old_bounds.push(self.bounds);
@@ -264,4 +607,32 @@ impl WindowInternal {
     pub(crate) fn take_old_bounds(&mut self) -> Option<Rect<u32>> {
         self.old_bounds.take()
     }
+
+    /// Keep a confined pointer inside of the client area, wherever it is now. The window
+    /// procedure calls this whenever the window moves or changes size.
+    pub(crate) fn update_cursor_clip(&self) {
+        if !self.confined.load(Ordering::Acquire) {
+            return;
+        }
+
+        // ClipCursor takes screen coordinates, so map the client area onto the screen
+        let hwnd = self.inner.hwnd().as_ptr();
+        let mut rect: RECT = unsafe { mem::zeroed() };
+        unsafe {
+            winuser::GetClientRect(hwnd, &mut rect);
+            winuser::MapWindowPoints(hwnd, ptr::null_mut(), &mut rect as *mut RECT as _, 2);
+            winuser::ClipCursor(&rect);
+        }
+    }
+
+    /// The constraints to hold the user to while they resize the window. Windows that
+    /// aren't resizable are pinned to their current size.
+    #[inline]
+    pub(crate) fn sizing_constraints(&self) -> SizeConstraints {
+        if self.resizable {
+            self.constraints
+        } else {
+            SizeConstraints::fixed(self.bounds.size)
+        }
+    }
 }
@@ -43,20 +43,26 @@
  * ----------------------------------------------------------------------------------
  */
 
+#[cfg(feature = "std")]
+use crate::Texture;
 use crate::{
+    instance::WeakInstance,
     mutexes::{Mutex, RwLock, RwLockReadGuard},
-    Event, EventData, EventType, Instance, Texture,
+    stats::timed,
+    Cursor, Event, EventData, EventType, Instance, PixelBuffer,
 };
 use alloc::{
     string::{String, ToString},
-    sync::Arc,
+    sync::{Arc, Weak},
+    vec::Vec,
 };
 use core::{
     any::Any,
     fmt,
     hash::{Hash, Hasher},
+    mem,
 };
-use euclid::default::Rect;
+use euclid::default::{Point2D, Rect, Size2D, Vector2D};
 use hashbrown::HashSet;
 #[cfg(feature = "std")]
 use parking_lot::MappedRwLockReadGuard;
@@ -70,6 +76,13 @@ use porcupine::{
 };
 #[cfg(debug_assertions)]
 use scopeguard::defer;
+use smallvec::SmallVec;
+
+mod builder;
+pub use builder::*;
+
+mod constraints;
+pub use constraints::*;
 
 mod id;
 pub(crate) use id::*;
@@ -79,7 +92,7 @@ pub use internal::EventHandler;
 pub(crate) use internal::*;
 
 // event types that are allowed no matter what
-const DEFAULT_EVENTS: [EventType; 11] = [
+const DEFAULT_EVENTS: [EventType; 20] = [
     EventType::NoOp,
     EventType::AboutToPaint,
     EventType::Paint,
@@ -87,12 +100,64 @@ const DEFAULT_EVENTS: [EventType; 11] = [
     EventType::TextChanged,
     EventType::Quit,
     EventType::Close,
+    EventType::Destroyed,
     EventType::BoundsChanging,
     EventType::BoundsChanged,
+    EventType::Moved,
+    EventType::Resized,
+    EventType::Shown,
+    EventType::Hidden,
+    EventType::Minimized,
+    EventType::Maximized,
+    EventType::Restored,
+    EventType::KeymapChanged,
     EventType::BackgroundChanging,
     EventType::BackgroundChanged,
 ];
 
+/// The display state of a window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WindowState {
+    /// The window is neither minimized nor maximized.
+    Normal,
+    /// The window has been minimized, or iconified.
+    Minimized,
+    /// The window has been maximized.
+    Maximized,
+}
+
+impl Default for WindowState {
+    #[inline]
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+// the visibility of a window, as reported by the backend
+#[derive(Debug, Default)]
+pub(crate) struct WindowStatus {
+    pub(crate) mapped: bool,
+    pub(crate) obscured: bool,
+    pub(crate) state: WindowState,
+    pub(crate) fullscreen: bool,
+    pub(crate) above: bool,
+    pub(crate) destroyed: bool,
+    pub(crate) position: Point2D<i32>,
+    pub(crate) cursor: Cursor,
+    // where the pointer was last seen, while in relative mouse mode
+    pub(crate) relative_origin: Option<Point2D<i32>>,
+    // the first half of a character from WM_CHAR that needed two UTF-16 code units
+    #[cfg(windows)]
+    pub(crate) high_surrogate: Option<u16>,
+}
+
+impl WindowStatus {
+    #[inline]
+    fn is_visible(&self) -> bool {
+        self.mapped && !self.obscured && self.state != WindowState::Minimized
+    }
+}
+
 /// A rectangle of pixels on the screen, in the most basic terms. This structure is actually
 /// a cheaply copyable wrapper around the internal window object.
 ///
@@ -118,7 +183,6 @@ const DEFAULT_EVENTS: [EventType; 11] = [
 ///                             "Hello world!".to_string(), // associated text
 ///                             rect(0, 0, 100, 100), // bounds
 ///                             None, // background
-///                             true, // is top level
 ///                         )?;
 ///
 /// // if cloned, they will still refer to the same window
@@ -132,13 +196,60 @@ const DEFAULT_EVENTS: [EventType; 11] = [
 pub struct Window {
     inner: Arc<RwLock<WindowInternal>>,
     handled_events: Arc<Mutex<HashSet<EventType>>>,
-    instance: Instance,
+    status: Arc<Mutex<WindowStatus>>,
+    instance: WeakInstance,
     id: usize,
 
     // make sure it owns any extra data
     _extra_data: Option<Arc<dyn Any>>,
 }
 
+/// A reference to a Window that does not keep it alive.
+///
+/// This is useful for storing references to other windows, such as a dialog's owner,
+/// without creating reference cycles.
+#[derive(Clone)]
+pub struct WeakWindow {
+    inner: Weak<RwLock<WindowInternal>>,
+    handled_events: Weak<Mutex<HashSet<EventType>>>,
+    status: Weak<Mutex<WindowStatus>>,
+    instance: WeakInstance,
+    id: usize,
+    _extra_data: Option<Weak<dyn Any>>,
+}
+
+impl fmt::Debug for WeakWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakWindow").field("id", &self.id).finish()
+    }
+}
+
+impl WeakWindow {
+    /// The ID of the window this refers to.
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Get the Window, if it is still alive.
+    #[inline]
+    pub fn upgrade(&self) -> Option<Window> {
+        let extra_data = match self._extra_data {
+            Some(ref ed) => Some(ed.upgrade()?),
+            None => None,
+        };
+
+        Some(Window::from_raw(
+            self.inner.upgrade()?,
+            self.handled_events.upgrade()?,
+            self.status.upgrade()?,
+            self.id,
+            self.instance.clone(),
+            extra_data,
+        ))
+    }
+}
+
 impl fmt::Debug for Window {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Window")
@@ -169,6 +280,7 @@ impl Clone for Window {
         Self::from_raw(
             self.inner.clone(),
             self.handled_events.clone(),
+            self.status.clone(),
             self.id,
             self.instance.clone(),
             self._extra_data.clone(),
@@ -178,9 +290,35 @@ impl Clone for Window {
 
 impl Window {
     /// The instance used to handle events and create this window.
+    ///
+    /// Windows do not keep their Instance alive, so this returns an error once
+    /// every handle to the Instance has been dropped. Use `try_instance` to get
+    /// an `Option` instead.
     #[inline]
-    pub fn instance(&self) -> &Instance {
-        &self.instance
+    pub fn instance(&self) -> crate::Result<Instance> {
+        self.try_instance().ok_or(crate::Error::StaticMsg(
+            "The window's Instance has been dropped",
+        ))
+    }
+
+    /// The instance used to handle events and create this window, or None if it
+    /// has been dropped.
+    #[inline]
+    pub fn try_instance(&self) -> Option<Instance> {
+        self.instance.upgrade()
+    }
+
+    /// Get a reference to this window that does not keep it alive.
+    #[inline]
+    pub fn downgrade(&self) -> WeakWindow {
+        WeakWindow {
+            inner: Arc::downgrade(&self.inner),
+            handled_events: Arc::downgrade(&self.handled_events),
+            status: Arc::downgrade(&self.status),
+            instance: self.instance.clone(),
+            id: self.id,
+            _extra_data: self._extra_data.as_ref().map(Arc::downgrade),
+        }
     }
 
     /// Internal function to create a new Window.
@@ -188,13 +326,15 @@ impl Window {
     pub(crate) fn from_raw(
         inner: Arc<RwLock<WindowInternal>>,
         handled_events: Arc<Mutex<HashSet<EventType>>>,
+        status: Arc<Mutex<WindowStatus>>,
         id: usize,
-        instance: Instance,
+        instance: WeakInstance,
         extra_data: Option<Arc<dyn Any>>,
     ) -> Self {
         Self {
             inner,
             handled_events,
+            status,
             id,
             instance,
             _extra_data: extra_data,
@@ -220,10 +360,10 @@ impl Window {
     /// # fn main() -> beetle::Result<()> {
     /// const TEST_TEXT: &'static str = "Test!";
     /// let instance = Instance::new()?;
-    /// let w = instance.create_window(None, TEST_TEXT.into_string(), rect(0, 0, 200, 100), None, true)?;
+    /// let w = instance.create_window(None, TEST_TEXT.to_string(), rect(0, 0, 200, 100), None)?;
     ///
-    /// let txt = w.text().into_string();
-    /// assert_eq!(txt, TEST_TEXT.into_string());
+    /// let txt = w.text()?.to_string();
+    /// assert_eq!(txt, TEST_TEXT);
     /// # Ok(())
     /// # }
     /// ```
@@ -234,7 +374,7 @@ impl Window {
         self.inner
             .try_read()
             .map(|i| RwLockReadGuard::map(i, |i| i.text()))
-            .ok_or_else(|| crate::Error::UnableToRead)
+            .ok_or(crate::Error::UnableToRead)
     }
 
     /// Set the text associated with this window. This will emit a TextChanged event.
@@ -242,14 +382,11 @@ impl Window {
     pub fn set_text(&self, text: String) -> crate::Result<()> {
         #[cfg(debug_assertions)]
         log::trace!("Locked read access for \"set_text\"");
-        let l = self
-            .inner
-            .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)?;
+        let l = self.inner.try_read().ok_or(crate::Error::UnableToRead)?;
         #[cfg(debug_assertions)]
         defer!(log::trace!("Unlocked read access for \"set_text\""));
 
-        self.instance.queue_event(Event::new(
+        self.instance()?.queue_event(Event::new(
             self,
             EventData::TextChanging {
                 old: l.text().to_string(),
@@ -262,17 +399,14 @@ impl Window {
     fn set_text_internal(&self, text: String) -> crate::Result<()> {
         #[cfg(debug_assertions)]
         log::trace!("Locked write access for \"set_text_internal\"");
-        let mut l = self
-            .inner
-            .try_write()
-            .ok_or_else(|| crate::Error::UnableToWrite)?;
+        let mut l = self.inner.try_write().ok_or(crate::Error::UnableToWrite)?;
         #[cfg(debug_assertions)]
         defer!(log::trace!(
             "Unlocked write access for \"set_text_internal\""
         ));
 
         let cloned_text = text.clone();
-        self.instance.queue_event(Event::new(
+        self.instance()?.queue_event(Event::new(
             self,
             EventData::TextChanged {
                 old: l.set_text(text)?,
@@ -289,10 +423,7 @@ impl Window {
     pub fn set_event_handler<F: EventHandler>(&self, evh: F) -> crate::Result<()> {
         #[cfg(debug_assertions)]
         log::trace!("Locked write access for \"set_event_handler\"");
-        let mut l = self
-            .inner
-            .try_write()
-            .ok_or_else(|| crate::Error::UnableToWrite)?;
+        let mut l = self.inner.try_write().ok_or(crate::Error::UnableToWrite)?;
         #[cfg(debug_assertions)]
         defer!(log::trace!(
             "Unlocked write access for \"set_event_handler\""
@@ -311,20 +442,27 @@ impl Window {
                 self.set_bounds_internal(*new, bools.0, bools.1)?
             }
             EventData::TextChanging { old: _, ref new } => self.set_text_internal(new.clone())?,
-            EventData::AboutToPaint => self.repaint(None)?,
+            // don't bother repainting windows that nobody can see: unmapped, minimized
+            // or completely covered ones
+            EventData::AboutToPaint if self.is_visible() => self.repaint(None)?,
             _ => { /* do nothing */ }
         }
 
         #[cfg(debug_assertions)]
         log::trace!("Locked read access for \"handle_event\"");
-        let l = self
-            .inner
-            .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)?;
+        let l = self.inner.try_read().ok_or(crate::Error::UnableToRead)?;
         #[cfg(debug_assertions)]
         defer!(log::trace!("Unlocked read access for \"handle_event\""));
 
-        l.handle_event(event)
+        match self.try_instance() {
+            Some(instance) => {
+                let clock = instance.clock();
+                let (res, elapsed) = timed(&*clock, || l.handle_event(event));
+                instance.record_handler_time(self.id(), elapsed);
+                res
+            }
+            None => l.handle_event(event),
+        }
     }
 
     /// Get the background for this window.
@@ -335,10 +473,7 @@ impl Window {
             "Providing mutex lock \"background\" from window id {}",
             self.id()
         );
-        let l = self
-            .inner
-            .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)?;
+        let l = self.inner.try_read().ok_or(crate::Error::UnableToRead)?;
         match l.background() {
             None => Ok(None),
             // Note: the unwrap() here will never panic. The read guard prevents write
@@ -357,16 +492,35 @@ impl Window {
 
         #[cfg(debug_assertions)]
         log::trace!("Locked read access for \"receive_events\"");
-        let l = self
-            .inner
-            .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)?;
+        let l = self.inner.try_read().ok_or(crate::Error::UnableToRead)?;
         #[cfg(debug_assertions)]
         defer!(log::trace!("Unlocked read access for \"receive_events\""));
 
         l.receive_events(event_types)
     }
 
+    /// Set whether or not mouse motion should be reported using motion hints.
+    ///
+    /// When motion hints are enabled, the backend only reports a single `MouseMove` event
+    /// until the event loop has caught up with it, instead of reporting every movement of
+    /// the mouse. This avoids floods of `MouseMove` events when the application can't keep
+    /// up with them.
+    #[inline]
+    pub fn set_motion_hint(&self, hint: bool) -> crate::Result<()> {
+        #[cfg(debug_assertions)]
+        log::trace!("Locked write access for \"set_motion_hint\"");
+        self.inner
+            .try_write()
+            .ok_or(crate::Error::UnableToWrite)?
+            .set_motion_hint(hint);
+        #[cfg(debug_assertions)]
+        log::trace!("Unlocked write access for \"set_motion_hint\"");
+
+        // re-select the events so the new setting takes effect
+        let event_types: Vec<EventType> = self.handled_events.lock().iter().copied().collect();
+        self.inner_window()?.receive_events(&event_types)
+    }
+
     /// Does this window receive this event type?
     #[inline]
     pub fn receives_event(&self, event_type: &EventType) -> bool {
@@ -396,7 +550,7 @@ impl Window {
         Ok(self
             .inner
             .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)?
+            .ok_or(crate::Error::UnableToRead)?
             .is_top_level())
     }
 
@@ -410,7 +564,7 @@ impl Window {
 
         self.inner
             .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)
+            .ok_or(crate::Error::UnableToRead)
             .map(|i| i.bounds())
     }
 
@@ -422,10 +576,7 @@ impl Window {
     ) -> crate::Result<()> {
         #[cfg(debug_assertions)]
         log::trace!("Locked write access for \"set_bounds_internal\"");
-        let mut l = self
-            .inner
-            .try_write()
-            .ok_or_else(|| crate::Error::UnableToWrite)?;
+        let mut l = self.inner.try_write().ok_or(crate::Error::UnableToWrite)?;
         #[cfg(debug_assertions)]
         defer!(log::trace!(
             "Unlocked write access for \"set_bounds_internal\""
@@ -435,31 +586,27 @@ impl Window {
 
         if enqueue {
             log::trace!("Queueing new BoundsChanged event");
-            self.instance.queue_event(Event::new(
-                self,
-                EventData::BoundsChanged {
-                    old: old_bounds,
-                    new: bounds,
-                },
-            ));
+            self.instance()?
+                .queue_events(Event::bounds_changed(self, old_bounds, bounds));
         }
         Ok(())
     }
 
-    /// Set the bounds of the window.
+    /// Set the bounds of the window. The size is adjusted to fit the window's size
+    /// constraints.
     #[inline]
     pub fn set_bounds(&self, bounds: Rect<u32>) -> crate::Result<()> {
         // this should just send a BoundsChanging event through, since that calls
         // set_bounds_internal when dispatched
         #[cfg(debug_assertions)]
         log::trace!("Locked read access for \"set_bounds\"");
-        let l = self
-            .inner
-            .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)?;
+        let l = self.inner.try_read().ok_or(crate::Error::UnableToRead)?;
         #[cfg(debug_assertions)]
         defer!(log::trace!("Unlocked read access for \"set_bounds\""));
 
+        let mut bounds = bounds;
+        bounds.size = l.size_constraints().clamp(bounds.size);
+
         let mut ev = Event::new(
             self,
             EventData::BoundsChanging {
@@ -471,11 +618,65 @@ impl Window {
         // Note: The (true, true) tells the event handler to both
         // set this on the X11 backend and release a BoundsChanged event
         ev.set_hidden_data((true, true));
-        self.instance.queue_event(ev);
+        self.instance()?.queue_event(ev);
+
+        Ok(())
+    }
+
+    /// Get the limits on the size of this window.
+    #[inline]
+    pub fn size_constraints(&self) -> crate::Result<SizeConstraints> {
+        Ok(self.inner_window()?.size_constraints())
+    }
 
+    fn update_size_constraints<F>(&self, f: F) -> crate::Result<()>
+    where
+        F: FnOnce(&mut SizeConstraints),
+    {
+        #[cfg(debug_assertions)]
+        log::trace!("Locked write access for \"update_size_constraints\"");
+        let mut l = self.inner.try_write().ok_or(crate::Error::UnableToWrite)?;
+        #[cfg(debug_assertions)]
+        defer!(log::trace!(
+            "Unlocked write access for \"update_size_constraints\""
+        ));
+
+        let mut constraints = l.size_constraints();
+        f(&mut constraints);
+        l.set_size_constraints(constraints)?;
+        let bounds = l.bounds();
+        mem::drop(l);
+
+        // bring the current size in line with the new constraints
+        if constraints.clamp(bounds.size) != bounds.size {
+            self.set_bounds(bounds)?;
+        }
         Ok(())
     }
 
+    /// Set the minimum and maximum size of this window.
+    #[inline]
+    pub fn set_size_limits(
+        &self,
+        min: Option<Size2D<u32>>,
+        max: Option<Size2D<u32>>,
+    ) -> crate::Result<()> {
+        self.update_size_constraints(|c| c.set_limits(min, max))
+    }
+
+    /// Keep this window at a certain ratio of width to height.
+    #[inline]
+    pub fn set_aspect_ratio(&self, ratio: Option<(u32, u32)>) -> crate::Result<()> {
+        self.update_size_constraints(|c| c.set_aspect_ratio(ratio))
+    }
+
+    /// Make the size of this window change in steps, counted from the minimum size. This
+    /// is useful for windows that display a grid of characters.
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size2D<u32>>) -> crate::Result<()> {
+        self.update_size_constraints(|c| c.set_increments(increments))
+    }
+
     /// Display the window.
     #[inline]
     pub fn show(&self) -> crate::Result<()> {
@@ -483,8 +684,6 @@ impl Window {
         fn show_no_repaint(this: &Window) -> crate::Result<()> {
             cfg_if::cfg_if! {
                 if #[cfg(windows)] {
-                    use core::mem;
-
                     // clone the window out of the lock
                     let l = this.inner_window()?;
                     let w = l.inner_porc_window().weak_reference();
@@ -496,26 +695,368 @@ impl Window {
                  } else {
                     this.inner
                         .try_read()
-                        .ok_or_else(|| crate::Error::UnableToRead)?
+                        .ok_or(crate::Error::UnableToRead)?
                         .show()
                 }
             }
         }
 
         show_no_repaint(self)?;
-        self.instance.queue_event(Event::new(
+        self.instance()?.queue_event(Event::new(
             self,
             EventData::Paint(crate::Graphics::from_window(self)?),
         ));
         Ok(())
     }
 
+    /// Give the keyboard focus to this window.
+    ///
+    /// Once the backend has moved the focus, a `FocusIn` event is emitted for this window
+    /// and a `FocusOut` event is emitted for the window that previously had the focus.
+    #[inline]
+    pub fn focus(&self) -> crate::Result<()> {
+        self.inner_window()?.focus()
+    }
+
+    /// Tell if this window has the keyboard focus.
+    #[inline]
+    pub fn has_focus(&self) -> bool {
+        self.try_instance().is_some_and(|i| i.is_focused(self))
+    }
+
+    /// Minimize this window.
+    #[inline]
+    pub fn minimize(&self) -> crate::Result<()> {
+        self.set_state(WindowState::Minimized)
+    }
+
+    /// Maximize this window.
+    #[inline]
+    pub fn maximize(&self) -> crate::Result<()> {
+        self.set_state(WindowState::Maximized)
+    }
+
+    /// Return this window to its normal state if it is minimized or maximized.
+    #[inline]
+    pub fn restore(&self) -> crate::Result<()> {
+        self.set_state(WindowState::Normal)
+    }
+
+    // the backend reports the change back, which updates the status and emits events
+    #[inline]
+    fn set_state(&self, state: WindowState) -> crate::Result<()> {
+        let instance = self.instance()?;
+        self.inner_window()?.set_state(&instance, state)
+    }
+
+    /// Make this window cover the entire screen, or return it to normal.
+    #[inline]
+    pub fn set_fullscreen(&self, fullscreen: bool) -> crate::Result<()> {
+        let instance = self.instance()?;
+        self.inner
+            .try_write()
+            .ok_or(crate::Error::UnableToWrite)?
+            .set_fullscreen(&instance, fullscreen)?;
+
+        // Win32 doesn't tell us about this, so keep track of it ourselves
+        #[cfg(windows)]
+        {
+            self.status.lock().fullscreen = fullscreen;
+        }
+        Ok(())
+    }
+
+    /// Tell if this window covers the entire screen.
+    #[inline]
+    pub fn is_fullscreen(&self) -> bool {
+        self.status.lock().fullscreen
+    }
+
+    /// Keep this window above other windows, or stop doing so.
+    #[inline]
+    pub fn set_always_on_top(&self, above: bool) -> crate::Result<()> {
+        let instance = self.instance()?;
+        self.inner_window()?.set_always_on_top(&instance, above)?;
+
+        #[cfg(windows)]
+        {
+            self.status.lock().above = above;
+        }
+        Ok(())
+    }
+
+    /// Tell if this window is kept above other windows.
+    #[inline]
+    pub fn is_always_on_top(&self) -> bool {
+        self.status.lock().above
+    }
+
+    /// Set the icon that the window manager shows for this window, such as in the taskbar.
+    ///
+    /// Several sizes of the same image can be given, and the window manager picks the one
+    /// that fits best. An empty slice removes the icon. This fails if any of the images
+    /// has fewer bytes than its size needs.
+    #[inline]
+    pub fn set_icon(&self, icons: &[PixelBuffer]) -> crate::Result<()> {
+        // the backends hand the pixels to the system by size, so they have to all be there
+        if !icons.iter().all(PixelBuffer::is_complete) {
+            return Err(crate::Error::StaticMsg(
+                "Icon image has fewer bytes than its size needs",
+            ));
+        }
+
+        let instance = self.instance()?;
+        self.inner
+            .try_write()
+            .ok_or(crate::Error::UnableToWrite)?
+            .set_icon(&instance, icons)
+    }
+
+    /// Set the cursor shown while the mouse is over this window.
+    #[inline]
+    pub fn set_cursor(&self, cursor: Cursor) -> crate::Result<()> {
+        if let Cursor::Custom { ref image, .. } = cursor {
+            if !image.is_complete() {
+                return Err(crate::Error::StaticMsg(
+                    "Cursor image has fewer bytes than its size needs",
+                ));
+            }
+        }
+
+        let mut status = self.status.lock();
+        status.cursor = cursor.clone();
+
+        // the cursor stays hidden until relative mouse mode is turned off
+        if status.relative_origin.is_some() {
+            return Ok(());
+        }
+        mem::drop(status);
+        self.apply_cursor(&cursor)
+    }
+
+    #[inline]
+    fn apply_cursor(&self, cursor: &Cursor) -> crate::Result<()> {
+        let instance = self.instance()?;
+        self.inner
+            .try_write()
+            .ok_or(crate::Error::UnableToWrite)?
+            .set_cursor(&instance, cursor)
+    }
+
+    /// Send all mouse events to this window until `release_pointer` is called, even if the
+    /// mouse leaves it. If `confine` is true, the pointer also can't leave the window.
+    ///
+    /// This fails if the window isn't visible, or another application has grabbed the
+    /// pointer.
+    #[inline]
+    pub fn grab_pointer(&self, confine: bool) -> crate::Result<()> {
+        let instance = self.instance()?;
+        self.inner_window()?.grab_pointer(&instance, confine)
+    }
+
+    /// Stop sending all mouse events to this window.
+    #[inline]
+    pub fn release_pointer(&self) -> crate::Result<()> {
+        let instance = self.instance()?;
+        self.inner_window()?.release_pointer(&instance)
+    }
+
+    /// Turn relative mouse mode on or off.
+    ///
+    /// In relative mouse mode, the pointer is grabbed and hidden, and `MouseMove` events
+    /// are replaced by `MouseDelta` events that report how far the mouse has moved. The
+    /// pointer is moved back towards the center of the window as needed, so the deltas
+    /// keep coming even when the mouse would have hit the edge of the screen.
+    pub fn set_relative_mouse(&self, relative: bool) -> crate::Result<()> {
+        if relative == self.is_relative_mouse() {
+            return Ok(());
+        }
+
+        let instance = self.instance()?;
+        if relative {
+            let center = self.center()?;
+            self.grab_pointer(true)?;
+            let res = self
+                .apply_cursor(&Cursor::Hidden)
+                .and_then(|()| instance.set_pointer_position(self, center));
+
+            // don't leave the pointer grabbed and hidden if it didn't work out
+            if let Err(e) = res {
+                let cursor = self.status.lock().cursor.clone();
+                let _ = self.release_pointer();
+                let _ = self.apply_cursor(&cursor);
+                return Err(e);
+            }
+            self.status.lock().relative_origin = Some(center);
+        } else {
+            let cursor = {
+                let mut status = self.status.lock();
+                status.relative_origin = None;
+                status.cursor.clone()
+            };
+            self.release_pointer()?;
+            self.apply_cursor(&cursor)?;
+        }
+        Ok(())
+    }
+
+    /// Tell if this window is in relative mouse mode.
+    #[inline]
+    pub fn is_relative_mouse(&self) -> bool {
+        self.status.lock().relative_origin.is_some()
+    }
+
+    #[inline]
+    fn center(&self) -> crate::Result<Point2D<i32>> {
+        let size = self.bounds()?.size.cast::<i32>();
+        Ok(Point2D::new(size.width / 2, size.height / 2))
+    }
+
+    /// Work out how far the mouse has moved in relative mouse mode, given its new
+    /// location, and move it back to the center of the window if it's near an edge.
+    /// Returns `None` if the window isn't in relative mouse mode.
+    pub(crate) fn relative_motion(
+        &self,
+        instance: &Instance,
+        location: Point2D<i32>,
+    ) -> crate::Result<Option<Vector2D<i32>>> {
+        let mut status = self.status.lock();
+        let origin = match status.relative_origin {
+            Some(origin) => origin,
+            None => return Ok(None),
+        };
+
+        // keep the pointer well away from the edges, where it would stop moving
+        let center = self.center()?;
+        let offset = location - center;
+        if offset.x.abs() > center.x / 2 || offset.y.abs() > center.y / 2 {
+            status.relative_origin = Some(center);
+            mem::drop(status);
+            instance.set_pointer_position(self, center)?;
+        } else {
+            status.relative_origin = Some(location);
+        }
+
+        Ok(Some(location - origin))
+    }
+
+    /// Destroy this window, along with all of its children.
+    ///
+    /// Once the backend has destroyed the window, a `Destroyed` event is emitted and the
+    /// window is removed from the Instance. Handles to the window may still be held after
+    /// that, but the window itself is gone.
+    #[inline]
+    pub fn destroy(&self) -> crate::Result<()> {
+        let mut status = self.status.lock();
+        if status.destroyed {
+            return Ok(());
+        }
+        // the flag has to be set while the backend destroys the window, since Win32 sends
+        // WM_DESTROY before DestroyWindow returns. if the window is still there
+        // afterwards, it isn't destroyed after all
+        status.destroyed = true;
+        mem::drop(status);
+
+        let res = self.inner_window().and_then(|inner| inner.destroy());
+        if res.is_err() {
+            self.status.lock().destroyed = false;
+        }
+        res
+    }
+
+    /// Tell if this window has been destroyed, or is in the process of being destroyed.
+    #[inline]
+    pub fn is_destroyed(&self) -> bool {
+        self.status.lock().destroyed
+    }
+
+    /// Create the events for a window that the backend has destroyed, and remove it from
+    /// its instance.
+    pub(crate) fn destroyed(&self) -> SmallVec<[Event; 2]> {
+        self.status.lock().destroyed = true;
+        if let Some(instance) = self.try_instance() {
+            instance.window_destroyed(self);
+        }
+
+        let mut evs = SmallVec::new();
+        evs.push(Event::new(self, EventData::Destroyed));
+        evs
+    }
+
+    /// Tell if this window is currently visible on the screen.
+    ///
+    /// A window is visible if it is mapped, is not minimized and is not completely
+    /// covered by other windows.
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.status.lock().is_visible()
+    }
+
+    /// Get the display state of this window.
+    #[inline]
+    pub fn state(&self) -> WindowState {
+        self.status.lock().state
+    }
+
+    /// Get the position of the window. Unlike the origin of its bounds, this may be negative
+    /// if the window is partially off the left or top of the screen.
+    #[inline]
+    pub fn position(&self) -> Point2D<i32> {
+        self.status.lock().position
+    }
+
+    /// Record the position of the window reported by the backend, and create a `Moved`
+    /// event if it has changed.
+    pub(crate) fn update_position(&self, position: Point2D<i32>) -> Option<Event> {
+        let old = mem::replace(&mut self.status.lock().position, position);
+        if old == position {
+            None
+        } else {
+            Some(Event::new(self, EventData::Moved { old, new: position }))
+        }
+    }
+
+    /// Update the status of the window, and create the events that describe the change.
+    pub(crate) fn update_status<F>(&self, f: F) -> SmallVec<[Event; 2]>
+    where
+        F: FnOnce(&mut WindowStatus),
+    {
+        let mut status = self.status.lock();
+        let (was_visible, old_state) = (status.is_visible(), status.state);
+        f(&mut status);
+        let (is_visible, state) = (status.is_visible(), status.state);
+        mem::drop(status);
+
+        let mut evs = SmallVec::new();
+        if state != old_state {
+            evs.push(Event::new(
+                self,
+                match state {
+                    WindowState::Normal => EventData::Restored,
+                    WindowState::Minimized => EventData::Minimized,
+                    WindowState::Maximized => EventData::Maximized,
+                },
+            ));
+        }
+        if is_visible != was_visible {
+            evs.push(Event::new(
+                self,
+                if is_visible {
+                    EventData::Shown
+                } else {
+                    EventData::Hidden
+                },
+            ));
+        }
+        evs
+    }
+
     /// Force a repaint operation on the window.
     #[inline]
     pub fn repaint(&self, bounds: Option<Rect<u32>>) -> crate::Result<()> {
         self.inner
             .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)?
+            .ok_or(crate::Error::UnableToRead)?
             .repaint(bounds)
     }
 }
@@ -525,14 +1066,29 @@ impl Window {
     pub(crate) fn inner_window(
         &self,
     ) -> crate::Result<RwLockReadGuard<'_, internal::WindowInternal>> {
-        self.inner
-            .try_read()
-            .ok_or_else(|| crate::Error::UnableToRead)
+        self.inner.try_read().ok_or(crate::Error::UnableToRead)
     }
 }
 
 #[cfg(windows)]
 impl Window {
+    /// Turn a UTF-16 code unit from WM_CHAR into a character. Characters outside of the
+    /// Basic Multilingual Plane arrive in two halves, so the first half is held on to
+    /// until the second one comes in.
+    pub(crate) fn porcupine_decode_char(&self, unit: u16) -> Option<char> {
+        let mut status = self.status.lock();
+        if (0xD800..0xDC00).contains(&unit) {
+            status.high_surrogate = Some(unit);
+            return None;
+        }
+
+        // a high surrogate that isn't followed by a low one is dropped
+        let high = status.high_surrogate.take();
+        core::char::decode_utf16(high.into_iter().chain(core::iter::once(unit)))
+            .filter_map(Result::ok)
+            .last()
+    }
+
     #[inline]
     pub(crate) fn store_old_bounds(&self) -> crate::Result<()> {
         #[cfg(debug_assertions)]
@@ -544,7 +1100,7 @@ impl Window {
 
         self.inner
             .try_write()
-            .ok_or_else(|| crate::Error::UnableToWrite)?
+            .ok_or(crate::Error::UnableToWrite)?
             .store_old_bounds();
         Ok(())
     }
@@ -559,7 +1115,7 @@ impl Window {
         Ok(self
             .inner
             .try_write()
-            .ok_or_else(|| crate::Error::UnableToRead)?
+            .ok_or(crate::Error::UnableToRead)?
             .take_old_bounds())
     }
 }
@@ -45,14 +45,16 @@
 
 #![cfg(windows)]
 
-use crate::{Event, Instance, Window};
+use crate::{instance::WeakInstance, Event, Instance, Window};
+use alloc::boxed::Box;
 use core::{any::Any, mem};
 use maybe_uninit::MaybeUninit;
 use porcupine::winapi::{
     shared::{
         basetsd::LONG_PTR,
-        minwindef::{FALSE, LPARAM, LRESULT, TRUE, UINT, WPARAM},
-        windef::{HBRUSH, HWND},
+        minwindef::{DWORD, FALSE, LPARAM, LRESULT, TRUE, UINT, WPARAM},
+        ntdef::LONG,
+        windef::{HBRUSH, HWND, RECT},
     },
     um::{errhandlingapi, winuser::*},
 };
@@ -79,9 +81,9 @@ pub unsafe extern "system" fn beetle_wndproc(
      *  Here is the system I'm trying here:
      *
      * 1). When the Window object is created, the GWLP_USERDATA pointer within the HWND
-     *     is set to a leaked instance of a clone of the Window. The Window clone also
-     *     contains a reference to the current Instance, which ensures we have both
-     *     during the Window Procedure.
+     *     is set to a boxed weak reference to the current Instance. The box is freed
+     *     along with the window, in WM_NCDESTROY. The reference is weak so that windows
+     *     don't keep their own Instance alive.
      * 2). When the Instance::next_event() function is called, it will call the usual
      *     Win32 event loop (e.g. GetMessage, TranslateMessage, DispatchMessage). The
      *     DispatchMessage function should call this window procedure. Note that all of
@@ -155,9 +157,20 @@ pub unsafe extern "system" fn beetle_wndproc(
         return DefWindowProcA(hwnd, msg, wparam, lparam);
     }
 
-    // transmute to a reference to the window
-    let instance = mem::transmute::<LONG_PTR, *const Instance>(instance);
-    let instance: &Instance = &*instance;
+    // the window owns the boxed reference to the instance, so free it with the window
+    if msg == WM_NCDESTROY {
+        SetWindowLongPtrA(hwnd, GWLP_USERDATA, 0);
+        mem::drop(Box::from_raw(instance as *mut WeakInstance));
+        return DefWindowProcA(hwnd, msg, wparam, lparam);
+    }
+
+    // transmute to a reference to the instance, which may have already been dropped
+    let instance = mem::transmute::<LONG_PTR, *const WeakInstance>(instance);
+    let instance = match (*instance).upgrade() {
+        Some(instance) => instance,
+        None => return DefWindowProcA(hwnd, msg, wparam, lparam),
+    };
+    let instance: &Instance = &instance;
 
     let window = match instance.porcupine_get_window(hwnd) {
         Some(w) => w,
@@ -175,22 +188,74 @@ pub unsafe extern "system" fn beetle_wndproc(
 
     // some basic handling, now that we have the window
     match msg {
-        WM_CLOSE => {
-            DestroyWindow(hwnd);
-        }
-        WM_DESTROY => {
-            if match window.is_top_level() {
-                Ok(tl) => tl,
+        // windows destroyed through Window::destroy() shouldn't end the application, and
+        // neither should windows that the quit policy doesn't care about
+        WM_DESTROY if !window.is_destroyed() => {
+            if match instance.window_closed(&window) {
+                Ok(quit) => quit,
                 Err(e) => {
                     log::error!(
-                        "Unable to determine whether the window is top level: {}. Assuming that it isn't.", 
+                        "Unable to determine whether closing the window should quit: {}. Assuming that it shouldn't.",
                         e
                     );
                     false
                 }
             } {
                 PostQuitMessage(0);
-                return 0;
+            }
+        }
+        // keep resizing by the user within the window's size constraints
+        WM_SIZING => {
+            if let Ok(constraints) = window.inner_window().map(|i| i.sizing_constraints()) {
+                // the constraints are on the client area, but this is the whole frame
+                let mut frame: RECT = mem::zeroed();
+                AdjustWindowRectEx(
+                    &mut frame,
+                    GetWindowLongW(hwnd, GWL_STYLE) as DWORD,
+                    FALSE,
+                    GetWindowLongW(hwnd, GWL_EXSTYLE) as DWORD,
+                );
+                let extra = euclid::size2(frame.right - frame.left, frame.bottom - frame.top);
+
+                let rect = &mut *(lparam as *mut RECT);
+                let size = euclid::size2(
+                    (rect.right - rect.left - extra.width).max(0) as u32,
+                    (rect.bottom - rect.top - extra.height).max(0) as u32,
+                );
+                let size = constraints.clamp(size);
+                let size = euclid::size2(
+                    size.width as LONG + extra.width,
+                    size.height as LONG + extra.height,
+                );
+
+                // move whichever edges are being dragged
+                let left = [WMSZ_LEFT, WMSZ_TOPLEFT, WMSZ_BOTTOMLEFT];
+                let top = [WMSZ_TOP, WMSZ_TOPLEFT, WMSZ_TOPRIGHT];
+                if left.iter().any(|&e| e as WPARAM == wparam) {
+                    rect.left = rect.right - size.width;
+                } else {
+                    rect.right = rect.left + size.width;
+                }
+                if top.iter().any(|&e| e as WPARAM == wparam) {
+                    rect.top = rect.bottom - size.height;
+                } else {
+                    rect.bottom = rect.top + size.height;
+                }
+
+                return TRUE as LRESULT;
+            }
+        }
+        // a confined pointer has to follow the client area around
+        WM_MOVE | WM_SIZE => {
+            if let Ok(inner) = window.inner_window() {
+                inner.update_cursor_clip();
+            }
+        }
+        // show the window's own cursor over its client area, and let Windows handle the borders
+        WM_SETCURSOR if LOWORD(lparam as _) as LRESULT == HTCLIENT => {
+            if let Ok(inner) = window.inner_window() {
+                SetCursor(inner.cursor());
+                return TRUE as LRESULT;
             }
         }
         _ => (),
@@ -200,6 +265,12 @@ pub unsafe extern "system" fn beetle_wndproc(
     let events = Event::from_porc(instance, window, msg, wparam, lparam);
     instance.porcupine_set_next_events(events); // forward the error to the actual Rust part
 
+    // destroy the window after the Close event, since destroying it sends WM_DESTROY
+    if msg == WM_CLOSE {
+        DestroyWindow(hwnd);
+        return 0;
+    }
+
     // just forward the event to DefWindowProcA now
     DefWindowProcA(hwnd, msg, wparam, lparam)
 }
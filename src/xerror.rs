@@ -0,0 +1,155 @@
+/* -----------------------------------------------------------------------------------
+ * src/xerror.rs - Capture X protocol errors instead of letting Xlib abort
+ * beetle - Pull-based GUI framework.
+ * Copyright © 2020 not_a_seagull
+ *
+ * This project is licensed under either the Apache 2.0 license or the MIT license, at
+ * your option. For more information, please consult the LICENSE-APACHE or LICENSE-MIT
+ * files in the repository root.
+ * -----------------------------------------------------------------------------------
+ * MIT License:
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the “Software”), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED “AS IS”, WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+ * THE SOFTWARE.
+ * -----------------------------------------------------------------------------------
+ * Apache 2.0 License Declaration:
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ * ----------------------------------------------------------------------------------
+ */
+
+#![cfg(target_os = "linux")]
+
+use crate::mutexes::Mutex;
+use core::{
+    mem, ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use flutterbug::x11::xlib;
+use libc::{c_int, c_void};
+
+/// A protocol error reported by the X server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct ProtocolError {
+    error_code: u8,
+    request_code: u8,
+    minor_code: u8,
+    resource_id: u64,
+}
+
+// Xlib's error handlers are process-wide, so the state they record is as well.
+lazy_static::lazy_static! {
+    static ref PENDING_ERROR: Mutex<Option<ProtocolError>> = Mutex::new(None);
+}
+
+static CONNECTION_LOST: AtomicBool = AtomicBool::new(false);
+
+// XSetIOErrorExitHandler was added in libX11 1.7, so it's looked up at runtime instead of
+// being linked against
+type SetIOErrorExitHandler = unsafe extern "C" fn(
+    *mut xlib::Display,
+    Option<unsafe extern "C" fn(*mut xlib::Display, *mut c_void)>,
+    *mut c_void,
+);
+
+/// Record a protocol error so it can be returned from whichever of `next_event`,
+/// `check_errors` or window creation comes first.
+unsafe extern "C" fn error_handler(_dpy: *mut xlib::Display, ev: *mut xlib::XErrorEvent) -> c_int {
+    let ev = &*ev;
+    let error = ProtocolError {
+        error_code: ev.error_code,
+        request_code: ev.request_code,
+        minor_code: ev.minor_code,
+        resource_id: ev.resourceid,
+    };
+
+    // only the first error is kept, since the rest are usually caused by it
+    let mut pending = PENDING_ERROR.lock();
+    match *pending {
+        Some(_) => log::error!("Dropping X protocol error: {:?}", error),
+        None => *pending = Some(error),
+    }
+
+    0
+}
+
+/// Record that the connection to the X server is gone.
+unsafe extern "C" fn io_error_handler(_dpy: *mut xlib::Display) -> c_int {
+    log::error!("Lost the connection to the X server");
+    CONNECTION_LOST.store(true, Ordering::SeqCst);
+    0
+}
+
+/// Replaces Xlib's default exit handler, which calls exit().
+unsafe extern "C" fn io_exit_handler(_dpy: *mut xlib::Display, _user_data: *mut c_void) {}
+
+/// Install Beetle's error handlers.
+///
+/// Note: on versions of libX11 older than 1.7, Xlib still calls exit() once the I/O
+///       error handler returns.
+pub(crate) fn install(dpy: *mut xlib::Display) {
+    unsafe {
+        xlib::XSetErrorHandler(Some(error_handler));
+        xlib::XSetIOErrorHandler(Some(io_error_handler));
+
+        let sym = libc::dlsym(
+            libc::RTLD_DEFAULT,
+            b"XSetIOErrorExitHandler\0".as_ptr() as *const libc::c_char,
+        );
+        if sym.is_null() {
+            log::debug!("XSetIOErrorExitHandler is unavailable, a lost connection will exit");
+        } else {
+            let set_exit_handler = mem::transmute::<*mut c_void, SetIOErrorExitHandler>(sym);
+            set_exit_handler(dpy, Some(io_exit_handler), ptr::null_mut());
+        }
+    }
+}
+
+/// Check whether the connection to the X server has been lost.
+#[inline]
+pub(crate) fn connection_lost() -> bool {
+    CONNECTION_LOST.load(Ordering::SeqCst)
+}
+
+/// Return any error that has been recorded since the last check.
+#[inline]
+pub(crate) fn check() -> crate::Result<()> {
+    if connection_lost() {
+        return Err(crate::Error::ConnectionLost);
+    }
+
+    match PENDING_ERROR.lock().take() {
+        Some(e) => Err(crate::Error::Protocol {
+            error_code: e.error_code,
+            request_code: e.request_code,
+            minor_code: e.minor_code,
+            resource_id: e.resource_id,
+        }),
+        None => Ok(()),
+    }
+}